@@ -3,18 +3,36 @@ use std::path::{Path, PathBuf};
 use raw_window_handle::HasWindowHandle;
 
 use crate::dialog::{
-    FileFilter, FileFiltersBag, OpenMultipleFile, OpenSingleDir, OpenSingleFile, SaveSingleFile,
+    FileChoice, FileFilter, FileFiltersBag, FileRequirements, FileValidator, KnownDir,
+    OpenMultipleDir, OpenMultipleFile, OpenSingleDir, OpenSingleFile, OpenSingleFileOrDir,
+    OverwritePolicy, PathStyle, Preset, SaveSingleFile,
 };
-use crate::utils::UnsafeWindowHandle;
+use crate::utils::{UnsafeWindowHandle, expand_location, recall_dir};
 
 /// Builder for file dialogs.
 #[derive(Debug, Clone, Default)]
 pub struct FileDialogBuilder {
     pub filename: Option<String>,
     pub location: Option<PathBuf>,
+    pub location_base: Option<PathBuf>,
     pub filters: FileFiltersBag,
     pub owner: UnsafeWindowHandle,
     pub title: Option<String>,
+    pub show_hidden: Option<bool>,
+    pub can_create_directories: Option<bool>,
+    pub accept_label: Option<String>,
+    pub preview: bool,
+    pub large_preview: bool,
+    pub requirements: FileRequirements,
+    pub validator: Option<FileValidator>,
+    pub memory_key: Option<String>,
+    pub add_to_recent: bool,
+    pub path_style: PathStyle,
+    pub dedup_paths: bool,
+    pub choices: Vec<FileChoice>,
+    pub case_sensitive_filters: bool,
+    pub overwrite_policy: OverwritePolicy,
+    pub auto_extension: bool,
 }
 
 impl FileDialogBuilder {
@@ -37,12 +55,24 @@ impl FileDialogBuilder {
         self
     }
 
-    /// Sets the default directory that the dialog shows at open.
+    /// Sets the default directory that the dialog shows at open. It can be a `file://` URI, start
+    /// with `~` or `~user`, and contain `$VAR` or `${VAR}` variables. If it points at a file, the
+    /// dialog shows the directory of the file with its name filled in, unless a filename is set.
     pub fn set_location<P: AsRef<Path> + ?Sized>(mut self, path: &P) -> Self {
         self.location = Some(path.as_ref().to_path_buf());
         self
     }
 
+    /// Sets the default directory that the dialog shows at open to a well-known one, such as
+    /// [`KnownDir::Pictures`]. Its path is localized, so this is preferred over hardcoding
+    /// `~/Pictures`. If the directory cannot be found, the location is left unchanged.
+    pub fn set_location_known(mut self, dir: KnownDir) -> Self {
+        if let Some(path) = dir.path() {
+            self.location = Some(path);
+        }
+        self
+    }
+
     /// Resets the default directory that the dialog shows at open.
     /// If a location is not set, the dialog will probably go to the current working directory.
     pub fn reset_location(mut self) -> Self {
@@ -50,6 +80,57 @@ impl FileDialogBuilder {
         self
     }
 
+    /// Sets a key for the purpose of the dialog, such as `"export-png"`. If no location is set,
+    /// the dialog opens in the directory last used with the same key, which is remembered per
+    /// application under the XDG state directory.
+    pub fn set_memory_key(mut self, key: impl ToString) -> Self {
+        self.memory_key = Some(key.to_string());
+        self
+    }
+
+    /// Stops remembering the directory last used.
+    pub fn reset_memory_key(mut self) -> Self {
+        self.memory_key = None;
+        self
+    }
+
+    /// Sets whether dialogs that open or save files add the selected files to the recent files
+    /// list of the desktop, which GTK and KDE read. They can be listed again with
+    /// [`recent_files`](crate::recent_files). Only Linux and BSD keep such a list, so this does
+    /// nothing on other platforms. Defaults to `false`.
+    pub fn set_add_to_recent(mut self, add_to_recent: bool) -> Self {
+        self.add_to_recent = add_to_recent;
+        self
+    }
+
+    /// Sets how the selected paths are returned, such as relative to the root of a project.
+    /// Defaults to [`PathStyle::AsChosen`].
+    pub fn set_path_style(mut self, style: PathStyle) -> Self {
+        self.path_style = style;
+        self
+    }
+
+    /// Sets whether dialogs that select multiple paths drop the ones that are the same as an
+    /// earlier one, once shaped in the path style. Defaults to `false`.
+    pub fn set_dedup_paths(mut self, dedup_paths: bool) -> Self {
+        self.dedup_paths = dedup_paths;
+        self
+    }
+
+    /// Sets the directory that a relative location is resolved against, instead of the current
+    /// working directory.
+    pub fn set_location_base<P: AsRef<Path> + ?Sized>(mut self, path: &P) -> Self {
+        self.location_base = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Resets the directory that a relative location is resolved against to the current working
+    /// directory.
+    pub fn reset_location_base(mut self) -> Self {
+        self.location_base = None;
+        self
+    }
+
     /// Adds a file type filter. The filter must contains at least one extension, otherwise this
     /// method will be a no-op. For dialogs that open directories, this is also a no-op.
     pub fn add_filter<T, U, V>(mut self, name: T, extensions: V) -> Self
@@ -62,6 +143,45 @@ impl FileDialogBuilder {
         self
     }
 
+    /// Adds a file type filter of glob patterns matched against filenames, such as `Makefile`,
+    /// `*.tar.*` or `Dockerfile*`. The filter must contain at least one pattern, otherwise this
+    /// method will be a no-op. For dialogs that open directories, this is also a no-op.
+    pub fn add_pattern_filter<T, U, V>(mut self, name: T, patterns: V) -> Self
+    where
+        T: ToString,
+        U: ToString,
+        V: AsRef<[U]>,
+    {
+        self.filters.add_patterns(name, patterns);
+        self
+    }
+
+    /// Adds a file type filter of MIME types, such as `image/png` or `image/*`. They are resolved to
+    /// glob patterns with the shared-mime-info database, so this is a no-op if none of the types are
+    /// known, or if there is no database, as is usually the case on macOS and Windows.
+    pub fn add_mime_filter<T, U, V>(mut self, name: T, mime_types: V) -> Self
+    where
+        T: ToString,
+        U: ToString,
+        V: AsRef<[U]>,
+    {
+        self.filters.add_mime_filter(name, mime_types);
+        self
+    }
+
+    /// Sets whether file type filters match filenames case-sensitively. Defaults to `false`, so
+    /// a `jpg` filter also accepts `PHOTO.JPG`. Windows and kdialog always match case-insensitively.
+    pub fn set_case_sensitive_filters(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive_filters = case_sensitive;
+        self
+    }
+
+    /// Adds a ready-made file type filter, such as [`Preset::Images`] or [`Preset::AllFiles`].
+    pub fn add_preset(mut self, preset: Preset) -> Self {
+        self.filters.add_preset(preset);
+        self
+    }
+
     /// Adds a bunch of file type filters.
     pub fn add_filters<I>(mut self, filters: I) -> Self
     where
@@ -75,6 +195,170 @@ impl FileDialogBuilder {
     /// Removes all file type filters.
     pub fn reset_filters(mut self) -> Self {
         self.filters.items.clear();
+        self.filters.default = None;
+        self
+    }
+
+    /// Sets the filter that is selected when the dialog opens, by its index in the order the
+    /// filters were added. Defaults to the first filter.
+    pub fn set_default_filter(mut self, index: usize) -> Self {
+        self.filters.default = Some(index);
+        self
+    }
+
+    /// Sets whether the dialog shows hidden files, such as dotfiles. If not set, the default of the
+    /// implementation is used. Returns [`Error::Unsupported`](crate::Error::Unsupported) when
+    /// shown if the implementation cannot honor it.
+    pub fn set_show_hidden(mut self, show_hidden: bool) -> Self {
+        self.show_hidden = Some(show_hidden);
+        self
+    }
+
+    /// Sets whether users can create new directories in the dialog. If not set, the default of
+    /// the implementation is used. Returns [`Error::Unsupported`](crate::Error::Unsupported) when
+    /// shown if the implementation cannot honor it.
+    pub fn set_can_create_directories(mut self, can_create_directories: bool) -> Self {
+        self.can_create_directories = Some(can_create_directories);
+        self
+    }
+
+    /// Sets whether dialogs that open files show a preview of the selected file, such as a
+    /// thumbnail of an image. kdialog and macOS always have previews. Returns
+    /// [`Error::Unsupported`](crate::Error::Unsupported) when shown on Windows, or if zenity is
+    /// the only implementation available. Defaults to `false`.
+    pub fn set_preview(mut self, preview: bool) -> Self {
+        self.preview = preview;
+        self
+    }
+
+    /// Sets whether the preview is larger than usual. This is a no-op unless previews are enabled,
+    /// and only yad can enlarge them. Defaults to `false`.
+    pub fn set_large_preview(mut self, large_preview: bool) -> Self {
+        self.large_preview = large_preview;
+        self
+    }
+
+    /// Sets what save dialogs do when users choose a file that already exists. Defaults to
+    /// [`OverwritePolicy::Prompt`].
+    pub fn set_overwrite_policy(mut self, policy: OverwritePolicy) -> Self {
+        self.overwrite_policy = policy;
+        self
+    }
+
+    /// Sets whether save dialogs append the extension of the selected filter when users type a
+    /// filename without one, instead of asking them to try again. macOS and Windows always do
+    /// this. Defaults to `false`.
+    pub fn set_auto_extension(mut self, auto_extension: bool) -> Self {
+        self.auto_extension = auto_extension;
+        self
+    }
+
+    /// Adds a dropdown of extra options to dialogs that select files. The options are pairs of
+    /// IDs and labels, and the ID of the selected one is returned in
+    /// [`FileSelection::choices`](crate::FileSelection::choices). Returns
+    /// [`Error::Unsupported`](crate::Error::Unsupported) when shown on macOS and Windows, or if
+    /// kdialog is the only implementation available.
+    pub fn add_choice<T, U, V, W, X>(mut self, id: T, label: U, options: X, default: W) -> Self
+    where
+        T: ToString,
+        U: ToString,
+        V: ToString,
+        W: ToString,
+        X: IntoIterator<Item = (V, V)>,
+    {
+        self.choices.push(FileChoice {
+            id: id.to_string(),
+            label: label.to_string(),
+            options: options
+                .into_iter()
+                .map(|(id, label)| (id.to_string(), label.to_string()))
+                .collect(),
+            default: default.to_string(),
+        });
+        self
+    }
+
+    /// Adds a checkbox to dialogs that select files. Its value is returned in
+    /// [`FileSelection::choices`](crate::FileSelection::choices) as `"true"` or `"false"`.
+    pub fn add_checkbox<T, U>(mut self, id: T, label: U, default: bool) -> Self
+    where
+        T: ToString,
+        U: ToString,
+    {
+        self.choices.push(FileChoice {
+            id: id.to_string(),
+            label: label.to_string(),
+            options: vec![],
+            default: default.to_string(),
+        });
+        self
+    }
+
+    /// Removes all extra choices.
+    pub fn reset_choices(mut self) -> Self {
+        self.choices.clear();
+        self
+    }
+
+    /// Sets the label of the button that accepts the selection, such as "Import" or "Export".
+    /// This is a no-op for kdialog.
+    pub fn set_accept_label(mut self, label: impl ToString) -> Self {
+        self.accept_label = Some(label.to_string());
+        self
+    }
+
+    /// Resets the label of the button that accepts the selection to the default.
+    pub fn reset_accept_label(mut self) -> Self {
+        self.accept_label = None;
+        self
+    }
+
+    /// Sets whether the selected paths must exist. Windows and macOS already ensure this when
+    /// opening, but kdialog, zenity and yad do not.
+    pub fn set_must_exist(mut self, must_exist: bool) -> Self {
+        self.requirements.must_exist = must_exist;
+        self
+    }
+
+    /// Sets whether the selected paths must not exist yet, such as for a new project directory.
+    pub fn set_must_not_exist(mut self, must_not_exist: bool) -> Self {
+        self.requirements.must_not_exist = must_not_exist;
+        self
+    }
+
+    /// Sets whether the selected files must be readable, and the selected directories listable.
+    pub fn set_must_be_readable(mut self, must_be_readable: bool) -> Self {
+        self.requirements.must_be_readable = must_be_readable;
+        self
+    }
+
+    /// Sets whether files can be created in the directory of the selected paths. This asks
+    /// `access(2)` on Unix, and only reads the read-only attribute on Windows, where ACLs that deny
+    /// creating files are not noticed.
+    pub fn set_parent_must_be_writable(mut self, parent_must_be_writable: bool) -> Self {
+        self.requirements.parent_must_be_writable = parent_must_be_writable;
+        self
+    }
+
+    /// Sets whether the selected directories must be empty.
+    pub fn set_directory_must_be_empty(mut self, directory_must_be_empty: bool) -> Self {
+        self.requirements.directory_must_be_empty = directory_must_be_empty;
+        self
+    }
+
+    /// Sets a closure that checks the selected paths. If it returns an error message, the message
+    /// is shown as a warning and the dialog is reopened at the rejected path.
+    pub fn set_validator<F>(mut self, validate: F) -> Self
+    where
+        F: Fn(&[PathBuf]) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.validator = Some(FileValidator::new(validate));
+        self
+    }
+
+    /// Removes the closure that checks the selected paths.
+    pub fn reset_validator(mut self) -> Self {
+        self.validator = None;
         self
     }
 
@@ -92,44 +376,156 @@ impl FileDialogBuilder {
 
     /// Builds a dialog that let users to open one file.
     pub fn open_single_file(self) -> OpenSingleFile {
+        let (location, filename) = self.target();
+
         OpenSingleFile {
-            filename: self.filename,
-            location: self.location,
-            filters: self.filters,
+            filename,
+            location,
+            filters: self.filters.case_sensitive(self.case_sensitive_filters),
             owner: self.owner,
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            preview: self.preview,
+            large_preview: self.large_preview,
+            accept_label: self.accept_label,
+            requirements: self.requirements,
+            validator: self.validator,
+            memory_key: self.memory_key,
+            path_style: self.path_style,
+            add_to_recent: self.add_to_recent,
+            choices: self.choices,
             title: self.title.unwrap_or("Open a File".to_string()),
         }
     }
 
     /// Builds a dialog that let users to open multiple files.
     pub fn open_multiple_file(self) -> OpenMultipleFile {
+        let (location, filename) = self.target();
+
         OpenMultipleFile {
-            filename: self.filename,
-            location: self.location,
-            filters: self.filters,
+            filename,
+            location,
+            filters: self.filters.case_sensitive(self.case_sensitive_filters),
             owner: self.owner,
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            preview: self.preview,
+            large_preview: self.large_preview,
+            accept_label: self.accept_label,
+            requirements: self.requirements,
+            validator: self.validator,
+            memory_key: self.memory_key,
+            path_style: self.path_style,
+            dedup_paths: self.dedup_paths,
+            add_to_recent: self.add_to_recent,
+            choices: self.choices,
             title: self.title.unwrap_or("Open Files".to_string()),
         }
     }
 
     /// Builds a dialog that let users to open one directory.
     pub fn open_single_dir(self) -> OpenSingleDir {
+        let (location, filename) = self.target();
+
         OpenSingleDir {
-            filename: self.filename,
-            location: self.location,
+            filename,
+            location,
             owner: self.owner,
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label,
+            requirements: self.requirements,
+            validator: self.validator,
+            memory_key: self.memory_key,
+            path_style: self.path_style,
             title: self.title.unwrap_or("Open a Folder".to_string()),
         }
     }
 
+    /// Builds a dialog that let users to open multiple directories.
+    pub fn open_multiple_dir(self) -> OpenMultipleDir {
+        let (location, filename) = self.target();
+
+        OpenMultipleDir {
+            filename,
+            location,
+            owner: self.owner,
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label,
+            requirements: self.requirements,
+            validator: self.validator,
+            memory_key: self.memory_key,
+            path_style: self.path_style,
+            dedup_paths: self.dedup_paths,
+            title: self.title.unwrap_or("Open Folders".to_string()),
+        }
+    }
+
+    /// Builds a dialog that let users to open either one file or one directory. The filters only
+    /// apply to files. Implementations that cannot select both in the same dialog ask users which
+    /// one they want first.
+    pub fn open_single_file_or_dir(self) -> OpenSingleFileOrDir {
+        let (location, filename) = self.target();
+
+        OpenSingleFileOrDir {
+            filename,
+            location,
+            filters: self.filters.case_sensitive(self.case_sensitive_filters),
+            owner: self.owner,
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            preview: self.preview,
+            large_preview: self.large_preview,
+            accept_label: self.accept_label,
+            requirements: self.requirements,
+            validator: self.validator,
+            memory_key: self.memory_key,
+            path_style: self.path_style,
+            title: self.title.unwrap_or("Open a File or Folder".to_string()),
+        }
+    }
+
     /// Builds a dialog that let users to save one file.
     pub fn save_single_file(self) -> SaveSingleFile {
+        let (location, filename) = self.target();
+
         SaveSingleFile {
-            filename: self.filename,
-            location: self.location,
-            filters: self.filters,
+            filename,
+            location,
+            filters: self.filters.case_sensitive(self.case_sensitive_filters),
             owner: self.owner,
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label,
+            requirements: self.requirements,
+            validator: self.validator,
+            memory_key: self.memory_key,
+            path_style: self.path_style,
+            add_to_recent: self.add_to_recent,
+            choices: self.choices,
+            overwrite_policy: self.overwrite_policy,
+            auto_extension: self.auto_extension,
             title: self.title.unwrap_or("Save As".to_string()),
         }
     }
+
+    /// Expands the location, and splits it into a directory and a filename if it points at a file.
+    fn target(&self) -> (Option<PathBuf>, Option<String>) {
+        let Some(location) = &self.location else {
+            let location = self.memory_key.as_deref().and_then(recall_dir);
+            return (location, self.filename.clone());
+        };
+
+        let location = expand_location(location, self.location_base.as_deref());
+        if location.is_file() {
+            if let (Some(dir), Some(name)) = (location.parent(), location.file_name()) {
+                let filename = self.filename.clone();
+                let filename = filename.unwrap_or_else(|| name.to_string_lossy().into_owned());
+                return (Some(dir.to_path_buf()), Some(filename));
+            }
+        }
+
+        (Some(location), self.filename.clone())
+    }
 }
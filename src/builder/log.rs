@@ -0,0 +1,22 @@
+use crate::dialog::LogTail;
+
+/// Builder for log viewer dialogs.
+#[derive(Debug, Clone, Default)]
+pub struct LogDialogBuilder {
+    pub title: Option<String>,
+}
+
+impl LogDialogBuilder {
+    /// Sets the window title for the dialog.
+    pub fn set_title(mut self, title: impl ToString) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Builds a dialog that shows the text written to it and keeps scrolling to the end.
+    pub fn tail(self) -> LogTail {
+        LogTail {
+            title: self.title.unwrap_or("Log".to_string()),
+        }
+    }
+}
@@ -4,6 +4,15 @@ pub use message::*;
 pub mod file;
 pub use file::*;
 
+pub mod log;
+pub use log::*;
+
+pub mod progress;
+pub use progress::*;
+
+pub mod tray;
+pub use tray::*;
+
 /// Builder for dialogs.
 #[derive(Debug, Clone)]
 pub struct DialogBuilder(());
@@ -16,4 +25,16 @@ impl DialogBuilder {
     pub fn message() -> MessageDialogBuilder {
         MessageDialogBuilder::default()
     }
+
+    pub fn log() -> LogDialogBuilder {
+        LogDialogBuilder::default()
+    }
+
+    pub fn progress() -> ProgressDialogBuilder {
+        ProgressDialogBuilder::default()
+    }
+
+    pub fn tray() -> TrayDialogBuilder {
+        TrayDialogBuilder::default()
+    }
 }
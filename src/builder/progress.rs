@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use raw_window_handle::HasWindowHandle;
+
+use crate::dialog::{BusyIndicator, MultiProgress};
+use crate::utils::UnsafeWindowHandle;
+
+/// Builder for progress dialogs.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressDialogBuilder {
+    pub title: Option<String>,
+    pub text: String,
+    pub bars: Vec<String>,
+    pub cancellable: bool,
+    pub delay: Option<Duration>,
+    pub owner: UnsafeWindowHandle,
+}
+
+impl ProgressDialogBuilder {
+    /// Sets the window title for the dialog.
+    pub fn set_title(mut self, title: impl ToString) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Sets the message text shown above the progress bars.
+    pub fn set_text(mut self, text: impl ToString) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    /// Adds a named progress bar. The name is used to update the bar later.
+    pub fn add_bar(mut self, name: impl ToString) -> Self {
+        self.bars.push(name.to_string());
+        self
+    }
+
+    /// Adds a bunch of named progress bars.
+    pub fn add_bars<I>(mut self, names: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: ToString,
+    {
+        self.bars.extend(names.into_iter().map(|x| x.to_string()));
+        self
+    }
+
+    /// Removes all progress bars.
+    pub fn reset_bars(mut self) -> Self {
+        self.bars.clear();
+        self
+    }
+
+    /// Sets whether the dialog shows a button to cancel the operation. Defaults to `false`.
+    pub fn set_cancellable(mut self, cancellable: bool) -> Self {
+        self.cancellable = cancellable;
+        self
+    }
+
+    /// Sets how long a busy indicator waits before showing up, so that quick operations never
+    /// flash a window. Defaults to 500 milliseconds.
+    pub fn set_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Sets the owner of the dialog.
+    pub fn set_owner<W: HasWindowHandle>(mut self, window: &W) -> Self {
+        self.owner = UnsafeWindowHandle::new(window);
+        self
+    }
+
+    /// Resets the owner of the dialog to nothing.
+    pub fn reset_owner(mut self) -> Self {
+        self.owner = UnsafeWindowHandle::default();
+        self
+    }
+
+    /// Builds a dialog that shows one progress bar for each of the named items. Implementations
+    /// that cannot show multiple bars show the overall progress and the label of the item updated
+    /// most recently instead.
+    pub fn multiple(self) -> MultiProgress {
+        MultiProgress {
+            title: self.title.unwrap_or("Progress".to_string()),
+            text: self.text,
+            bars: self.bars,
+            cancellable: self.cancellable,
+            owner: self.owner,
+        }
+    }
+
+    /// Builds a dialog that shows a pulsating progress bar while some work is running.
+    pub fn busy(self) -> BusyIndicator {
+        BusyIndicator {
+            title: self.title.unwrap_or("Please Wait".to_string()),
+            text: self.text,
+            cancellable: self.cancellable,
+            delay: self.delay.unwrap_or(Duration::from_millis(500)),
+            owner: self.owner,
+        }
+    }
+}
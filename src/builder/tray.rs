@@ -0,0 +1,65 @@
+use crate::dialog::{TrayCallback, TrayIcon, TrayMenuItem};
+
+/// Builder for tray icons.
+#[derive(Debug, Clone, Default)]
+pub struct TrayDialogBuilder {
+    pub icon: Option<String>,
+    pub tooltip: String,
+    pub items: Vec<TrayMenuItem>,
+    pub on_click: Option<TrayCallback>,
+}
+
+impl TrayDialogBuilder {
+    /// Sets the icon, which is the name of an icon in the icon theme or a path to an image.
+    pub fn set_icon(mut self, icon: impl ToString) -> Self {
+        self.icon = Some(icon.to_string());
+        self
+    }
+
+    /// Sets the text shown when hovering over the icon.
+    pub fn set_tooltip(mut self, tooltip: impl ToString) -> Self {
+        self.tooltip = tooltip.to_string();
+        self
+    }
+
+    /// Adds an item to the menu of the icon. The callback is invoked on a background thread when
+    /// the item is clicked.
+    pub fn add_item<F>(mut self, label: impl ToString, callback: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.items.push(TrayMenuItem::new(label, callback));
+        self
+    }
+
+    /// Removes all items from the menu of the icon.
+    pub fn reset_items(mut self) -> Self {
+        self.items.clear();
+        self
+    }
+
+    /// Sets the callback invoked on a background thread when the icon is clicked.
+    pub fn set_on_click<F>(mut self, callback: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.on_click = Some(TrayCallback::new(callback));
+        self
+    }
+
+    /// Resets the callback invoked when the icon is clicked.
+    pub fn reset_on_click(mut self) -> Self {
+        self.on_click = None;
+        self
+    }
+
+    /// Builds a status icon in the system tray.
+    pub fn status_icon(self) -> TrayIcon {
+        TrayIcon {
+            icon: self.icon.unwrap_or("dialog-information".to_string()),
+            tooltip: self.tooltip,
+            items: self.items,
+            on_click: self.on_click,
+        }
+    }
+}
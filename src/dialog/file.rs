@@ -1,71 +1,833 @@
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use super::{Dialog, FileFiltersBag};
-use crate::utils::UnsafeWindowHandle;
+use super::{
+    Dialog, DialogImpl, FileFiltersBag, MessageAlert, MessageConfirm, MessageLevel,
+    add_recent_files,
+};
+use crate::Result;
+use crate::utils::{UnsafeWindowHandle, canonicalize, relative_path, remember_dir};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OpenSingleFile {
     pub filename: Option<String>,
     pub location: Option<PathBuf>,
     pub title: String,
     pub filters: FileFiltersBag,
     pub owner: UnsafeWindowHandle,
+    pub show_hidden: Option<bool>,
+    pub can_create_directories: Option<bool>,
+    pub preview: bool,
+    pub large_preview: bool,
+    pub accept_label: Option<String>,
+    pub requirements: FileRequirements,
+    pub validator: Option<FileValidator>,
+    pub memory_key: Option<String>,
+    pub path_style: PathStyle,
+    pub add_to_recent: bool,
+    pub choices: Vec<FileChoice>,
 }
 
 impl Dialog for OpenSingleFile {
     type Output = Option<PathBuf>;
 }
 
-impl OpenSingleFile {
-    super::dialog_delegate!();
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OpenMultipleFile {
     pub filename: Option<String>,
     pub location: Option<PathBuf>,
     pub title: String,
     pub filters: FileFiltersBag,
     pub owner: UnsafeWindowHandle,
+    pub show_hidden: Option<bool>,
+    pub can_create_directories: Option<bool>,
+    pub preview: bool,
+    pub large_preview: bool,
+    pub accept_label: Option<String>,
+    pub requirements: FileRequirements,
+    pub validator: Option<FileValidator>,
+    pub memory_key: Option<String>,
+    pub path_style: PathStyle,
+    pub dedup_paths: bool,
+    pub add_to_recent: bool,
+    pub choices: Vec<FileChoice>,
 }
 
 impl Dialog for OpenMultipleFile {
     type Output = Vec<PathBuf>;
 }
 
-impl OpenMultipleFile {
-    super::dialog_delegate!();
-}
-
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OpenSingleDir {
     pub filename: Option<String>,
     pub location: Option<PathBuf>,
     pub title: String,
     pub owner: UnsafeWindowHandle,
+    pub show_hidden: Option<bool>,
+    pub can_create_directories: Option<bool>,
+    pub accept_label: Option<String>,
+    pub requirements: FileRequirements,
+    pub validator: Option<FileValidator>,
+    pub memory_key: Option<String>,
+    pub path_style: PathStyle,
 }
 
 impl Dialog for OpenSingleDir {
     type Output = Option<PathBuf>;
 }
 
-impl OpenSingleDir {
-    super::dialog_delegate!();
+#[derive(Debug, Clone)]
+pub struct OpenMultipleDir {
+    pub filename: Option<String>,
+    pub location: Option<PathBuf>,
+    pub title: String,
+    pub owner: UnsafeWindowHandle,
+    pub show_hidden: Option<bool>,
+    pub can_create_directories: Option<bool>,
+    pub accept_label: Option<String>,
+    pub requirements: FileRequirements,
+    pub validator: Option<FileValidator>,
+    pub memory_key: Option<String>,
+    pub path_style: PathStyle,
+    pub dedup_paths: bool,
+}
+
+impl Dialog for OpenMultipleDir {
+    type Output = Vec<PathBuf>;
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenSingleFileOrDir {
+    pub filename: Option<String>,
+    pub location: Option<PathBuf>,
+    pub title: String,
+    pub filters: FileFiltersBag,
+    pub owner: UnsafeWindowHandle,
+    pub show_hidden: Option<bool>,
+    pub can_create_directories: Option<bool>,
+    pub preview: bool,
+    pub large_preview: bool,
+    pub accept_label: Option<String>,
+    pub requirements: FileRequirements,
+    pub validator: Option<FileValidator>,
+    pub memory_key: Option<String>,
+    pub path_style: PathStyle,
+}
+
+impl Dialog for OpenSingleFileOrDir {
+    type Output = Option<FileOrDir>;
+}
+
+/// A path selected in a dialog that accepts both files and directories.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileOrDir {
+    File(PathBuf),
+    Dir(PathBuf),
+}
+
+impl FileOrDir {
+    pub fn path(&self) -> &Path {
+        match self {
+            FileOrDir::File(path) => path,
+            FileOrDir::Dir(path) => path,
+        }
+    }
+
+    pub fn into_path_buf(self) -> PathBuf {
+        match self {
+            FileOrDir::File(path) => path,
+            FileOrDir::Dir(path) => path,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        matches!(self, FileOrDir::Dir(_))
+    }
+
+    /// Tags the first path of the selection with what users chose to select.
+    pub(crate) fn from_selection(selection: FileSelection) -> Option<Self> {
+        let is_dir = selection.is_dir;
+        let path = selection.into_path()?;
+
+        Some(if is_dir {
+            FileOrDir::Dir(path)
+        } else {
+            FileOrDir::File(path)
+        })
+    }
 }
 
-#[derive(Debug)]
+/// A well-known directory of the user, whose path depends on the platform and the locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KnownDir {
+    Home,
+    Desktop,
+    Documents,
+    Downloads,
+    Music,
+    Pictures,
+    Videos,
+    Temp,
+}
+
+impl KnownDir {
+    /// Looks up the directory, reading `user-dirs.dirs` on XDG systems. Returns `None` if it is
+    /// not configured.
+    pub fn path(self) -> Option<PathBuf> {
+        match self {
+            KnownDir::Home => dirs::home_dir(),
+            KnownDir::Desktop => dirs::desktop_dir(),
+            KnownDir::Documents => dirs::document_dir(),
+            KnownDir::Downloads => dirs::download_dir(),
+            KnownDir::Music => dirs::audio_dir(),
+            KnownDir::Pictures => dirs::picture_dir(),
+            KnownDir::Videos => dirs::video_dir(),
+            KnownDir::Temp => Some(std::env::temp_dir()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct SaveSingleFile {
     pub filename: Option<String>,
     pub location: Option<PathBuf>,
     pub title: String,
     pub filters: FileFiltersBag,
     pub owner: UnsafeWindowHandle,
+    pub show_hidden: Option<bool>,
+    pub can_create_directories: Option<bool>,
+    pub accept_label: Option<String>,
+    pub requirements: FileRequirements,
+    pub validator: Option<FileValidator>,
+    pub memory_key: Option<String>,
+    pub path_style: PathStyle,
+    pub add_to_recent: bool,
+    pub choices: Vec<FileChoice>,
+    pub overwrite_policy: OverwritePolicy,
+    pub auto_extension: bool,
 }
 
 impl Dialog for SaveSingleFile {
     type Output = Option<PathBuf>;
 }
 
-impl SaveSingleFile {
-    super::dialog_delegate!();
+/// What a save dialog does when users choose a file that already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Asks users whether to replace the file.
+    #[default]
+    Prompt,
+    /// Accepts the file without asking, unless the implementation always asks.
+    Allow,
+    /// Rejects the file and lets users choose another one.
+    Deny,
+}
+
+/// How the paths selected in a file dialog are returned.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PathStyle {
+    /// Returns the paths as the implementation reports them.
+    #[default]
+    AsChosen,
+    /// Resolves symlinks and `..` components. Files that do not exist yet are resolved through
+    /// their directory, and paths that cannot be resolved are returned as chosen.
+    Canonical,
+    /// Makes the paths relative to a directory, such as the root of a project, going out of it
+    /// with `..` if needed. Paths on other drives are returned as chosen.
+    RelativeTo(PathBuf),
+}
+
+impl PathStyle {
+    pub(crate) fn apply(&self, path: &Path) -> PathBuf {
+        let shaped = match self {
+            PathStyle::AsChosen => None,
+            PathStyle::Canonical => canonicalize(path),
+            PathStyle::RelativeTo(base) => relative_path(path, base),
+        };
+
+        shaped.unwrap_or_else(|| path.to_path_buf())
+    }
+}
+
+/// Paths selected in a file dialog, along with the filter that was selected at the time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileSelection {
+    pub paths: Vec<PathBuf>,
+    /// Index of the selected filter, in the order the filters were added. This is `None` if
+    /// there were no filters, or if the implementation could not tell which one was used.
+    pub filter_index: Option<usize>,
+    /// Values of the extra choices, keyed by their IDs. See [`FileChoice`] for the format.
+    pub choices: HashMap<String, String>,
+    /// Whether users chose to select directories rather than files. This is always `true` for
+    /// directory dialogs and always `false` for file dialogs.
+    pub is_dir: bool,
+    /// Whether the implementation already asked users to replace the file, if it exists.
+    pub(crate) overwrite_confirmed: bool,
+}
+
+impl FileSelection {
+    /// Infers the selected filter from the first path, for implementations that cannot report it.
+    #[cfg(unix)]
+    pub(crate) fn infer(paths: Vec<PathBuf>, filters: &FileFiltersBag) -> Self {
+        let filter_index = paths.first().and_then(|path| filters.position(path));
+        FileSelection {
+            paths,
+            filter_index,
+            choices: HashMap::new(),
+            is_dir: false,
+            overwrite_confirmed: false,
+        }
+    }
+
+    /// Creates a selection of directories, or `None` if there are no paths.
+    pub(crate) fn from_dirs(paths: Vec<PathBuf>) -> Option<Self> {
+        (!paths.is_empty()).then(|| FileSelection {
+            paths,
+            is_dir: true,
+            ..Default::default()
+        })
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.paths.first().map(PathBuf::as_path)
+    }
+
+    pub fn into_path(self) -> Option<PathBuf> {
+        self.paths.into_iter().next()
+    }
+}
+
+/// An extra option shown along with a file dialog, such as an encoding dropdown or a read-only
+/// checkbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChoice {
+    pub id: String,
+    pub label: String,
+    /// IDs and labels of the options of a dropdown. This is empty for a checkbox.
+    pub options: Vec<(String, String)>,
+    /// ID of the option selected at open, or `"true"` or `"false"` for a checkbox. This is also
+    /// the format of the value returned in [`FileSelection::choices`].
+    pub default: String,
+}
+
+impl FileChoice {
+    pub fn is_checkbox(&self) -> bool {
+        self.options.is_empty()
+    }
+}
+
+/// A closure that checks the paths selected in a file dialog. If it returns an error message, the
+/// message is shown as a warning and the dialog is reopened at the rejected path.
+#[derive(Clone)]
+pub struct FileValidator(Arc<ValidateFn>);
+
+type ValidateFn = dyn Fn(&[PathBuf]) -> std::result::Result<(), String> + Send + Sync;
+
+impl FileValidator {
+    pub fn new<F>(validate: F) -> Self
+    where
+        F: Fn(&[PathBuf]) -> std::result::Result<(), String> + Send + Sync + 'static,
+    {
+        FileValidator(Arc::new(validate))
+    }
+
+    pub fn validate(&self, paths: &[PathBuf]) -> std::result::Result<(), String> {
+        (self.0)(paths)
+    }
+}
+
+impl Debug for FileValidator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FileValidator")
+    }
+}
+
+/// Checks on the filesystem that the selected paths must pass. A path that fails one of them is
+/// rejected with a message, and the dialog is reopened at the path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileRequirements {
+    pub must_exist: bool,
+    pub must_not_exist: bool,
+    /// Files must be openable for reading, and directories must be listable.
+    pub must_be_readable: bool,
+    /// The directory containing the path must allow creating files in it.
+    pub parent_must_be_writable: bool,
+    /// Directories must have no entries. Paths that are not directories pass.
+    pub directory_must_be_empty: bool,
+}
+
+impl FileRequirements {
+    /// Returns a message about the first path that fails a check, if any.
+    pub fn check(&self, paths: &[PathBuf]) -> Option<String> {
+        paths.iter().find_map(|path| self.check_path(path))
+    }
+
+    fn check_path(&self, path: &Path) -> Option<String> {
+        let name = display_name(path);
+        let exists = path.exists();
+
+        if self.must_exist && !exists {
+            return Some(format!("\"{name}\" does not exist."));
+        }
+
+        if self.must_not_exist && exists {
+            return Some(format!("\"{name}\" already exists."));
+        }
+
+        if self.must_be_readable && exists && !is_readable(path) {
+            return Some(format!("\"{name}\" cannot be read."));
+        }
+
+        if self.parent_must_be_writable {
+            let parent = path.parent().unwrap_or(path);
+            if !is_writable_dir(parent) {
+                let parent = display_name(parent);
+                return Some(format!("Cannot create files in \"{parent}\"."));
+            }
+        }
+
+        if self.directory_must_be_empty && path.is_dir() && !is_empty_dir(path) {
+            return Some(format!("\"{name}\" is not empty."));
+        }
+
+        None
+    }
+}
+
+fn display_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => path.display().to_string(),
+    }
+}
+
+fn is_readable(path: &Path) -> bool {
+    if path.is_dir() {
+        std::fs::read_dir(path).is_ok()
+    } else {
+        std::fs::File::open(path).is_ok()
+    }
+}
+
+/// Asks the system rather than reading the permission bits, which do not tell about ACLs or
+/// read-only mounts. Creating files takes both write and search permissions on the directory.
+#[cfg(unix)]
+fn is_writable_dir(dir: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(path) = CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+
+    dir.is_dir() && unsafe { libc::access(path.as_ptr(), libc::W_OK | libc::X_OK) } == 0
+}
+
+/// Only the read-only attribute is looked at, so directories whose ACLs deny creating files pass.
+#[cfg(not(unix))]
+fn is_writable_dir(dir: &Path) -> bool {
+    std::fs::metadata(dir).is_ok_and(|x| x.is_dir() && !x.permissions().readonly())
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    match std::fs::read_dir(path) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(_) => false,
+    }
+}
+
+/// Implemented by each platform to show a file dialog and return the selection.
+pub(crate) trait SelectImpl: Sized {
+    fn select(self) -> Result<Option<FileSelection>>;
+
+    #[cfg(feature = "async")]
+    fn spawn_select(self) -> impl Future<Output = Result<Option<FileSelection>>> + Send;
+
+    /// Judges the selection on what the implementation does not check by itself.
+    fn judge(&self, _selection: &FileSelection) -> Verdict {
+        Verdict::Accept
+    }
+
+    /// Completes a selection once it has been accepted, such as by asking for the extra choices
+    /// in a separate form. Returns `None` if users cancelled.
+    fn finish(self, selection: FileSelection) -> Result<Option<FileSelection>> {
+        Ok(Some(selection))
+    }
+
+    #[cfg(feature = "async")]
+    fn spawn_finish(
+        self,
+        selection: FileSelection,
+    ) -> impl Future<Output = Result<Option<FileSelection>>> + Send {
+        async move { Ok(Some(selection)) }
+    }
+}
+
+/// Whether a file dialog returns a selection, or reopens at it.
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+pub(crate) enum Verdict {
+    Accept,
+    /// Shows the message as a warning and reopens the dialog.
+    Reject(String),
+    /// Asks users to confirm the selection with the message, and reopens the dialog if they don't.
+    Confirm(String),
+}
+
+/// What file dialogs do on top of the selection of the platform.
+trait FileDialog: SelectImpl + Clone + Send {
+    fn owner(&self) -> &UnsafeWindowHandle;
+
+    fn requirements(&self) -> &FileRequirements;
+
+    fn validator(&self) -> Option<&FileValidator>;
+
+    fn memory_key(&self) -> Option<&str>;
+
+    fn path_style(&self) -> &PathStyle;
+
+    fn add_to_recent(&self) -> bool {
+        false
+    }
+
+    fn dedup_paths(&self) -> bool {
+        false
+    }
+
+    fn reopen_at(&mut self, path: &Path);
+
+    /// Decides whether the selection can be used. Rejections come before confirmations, so that
+    /// users are not asked about a selection that is then rejected anyway.
+    fn check(&self, selection: &FileSelection) -> Verdict {
+        let verdict = self.judge(selection);
+        if let Verdict::Reject(_) = verdict {
+            return verdict;
+        }
+
+        let message = self.requirements().check(&selection.paths).or_else(|| {
+            let validator = self.validator()?;
+            validator.validate(&selection.paths).err()
+        });
+
+        match message {
+            Some(message) => Verdict::Reject(message),
+            None => verdict,
+        }
+    }
+
+    /// Remembers the directory of the selection for the next dialog with the same key, and adds
+    /// the files to the recent files list if asked to. Failing to do so should not lose the
+    /// selection, so errors are ignored.
+    fn record(&self, selection: &FileSelection) {
+        if self.add_to_recent() {
+            let _ = add_recent_files(&selection.paths);
+        }
+
+        let (Some(key), Some(path)) = (self.memory_key(), selection.path()) else {
+            return;
+        };
+
+        if let Some(dir) = path.parent() {
+            let _ = remember_dir(key, dir);
+        }
+    }
+
+    /// Shapes the paths in the style asked for, once they have been checked and recorded.
+    fn shape(&self, mut selection: FileSelection) -> FileSelection {
+        let style = self.path_style();
+        for path in &mut selection.paths {
+            *path = style.apply(path);
+        }
+
+        if self.dedup_paths() {
+            let mut seen = HashSet::new();
+            selection.paths.retain(|x| seen.insert(x.clone()));
+        }
+
+        selection
+    }
+
+    fn warn(&self, message: String) -> MessageAlert {
+        MessageAlert {
+            title: String::from("Invalid Selection"),
+            text: message,
+            level: MessageLevel::Warning,
+            owner: self.owner().clone(),
+        }
+    }
+
+    fn confirm(&self, message: String) -> MessageConfirm {
+        MessageConfirm {
+            title: String::from("Confirm Selection"),
+            text: message,
+            level: MessageLevel::Warning,
+            owner: self.owner().clone(),
+        }
+    }
+
+    fn run(mut self) -> Result<Option<FileSelection>> {
+        loop {
+            let Some(selection) = self.clone().select()? else {
+                return Ok(None);
+            };
+
+            let accepted = match self.check(&selection) {
+                Verdict::Accept => true,
+                Verdict::Reject(message) => {
+                    self.warn(message).show()?;
+                    false
+                }
+                Verdict::Confirm(message) => self.confirm(message).show()?,
+            };
+
+            if accepted {
+                let Some(selection) = self.clone().finish(selection)? else {
+                    return Ok(None);
+                };
+
+                self.record(&selection);
+                return Ok(Some(self.shape(selection)));
+            }
+
+            if let Some(path) = selection.path() {
+                self.reopen_at(path);
+            }
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn spawn_run(mut self) -> impl Future<Output = Result<Option<FileSelection>>> + Send {
+        async move {
+            loop {
+                let Some(selection) = self.clone().spawn_select().await? else {
+                    return Ok(None);
+                };
+
+                let accepted = match self.check(&selection) {
+                    Verdict::Accept => true,
+                    Verdict::Reject(message) => {
+                        self.warn(message).spawn().await?;
+                        false
+                    }
+                    Verdict::Confirm(message) => self.confirm(message).spawn().await?,
+                };
+
+                if accepted {
+                    let Some(selection) = self.clone().spawn_finish(selection).await? else {
+                        return Ok(None);
+                    };
+
+                    self.record(&selection);
+                    return Ok(Some(self.shape(selection)));
+                }
+
+                if let Some(path) = selection.path() {
+                    self.reopen_at(path);
+                }
+            }
+        }
+    }
+}
+
+/// Creates a selection of the paths, or `None` if there are none, for implementations that cannot
+/// report the selected filter, such as the GNU ones and macOS open panels.
+#[cfg(unix)]
+pub(crate) fn infer_selection(
+    paths: impl IntoIterator<Item = PathBuf>,
+    filters: &FileFiltersBag,
+) -> Option<FileSelection> {
+    let paths: Vec<PathBuf> = paths.into_iter().collect();
+    (!paths.is_empty()).then(|| FileSelection::infer(paths, filters))
+}
+
+/// Fails for implementations that have no place for extra choices yet.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+pub(crate) fn ensure_no_choices(choices: &[FileChoice]) -> Result {
+    if !choices.is_empty() {
+        return Err(crate::Error::Unsupported("extra choices"));
+    }
+
+    Ok(())
+}
+
+macro_rules! file_dialog {
+    ($dialog:ident $(, $flag:ident)*, |$selection:ident| $output:expr) => {
+        impl $dialog {
+            super::dialog_delegate!();
+
+            /// Shows the dialog and returns the selection along with the filter it was made with,
+            /// or `None` if the dialog was cancelled.
+            pub fn show_selection(self) -> Result<Option<FileSelection>> {
+                FileDialog::run(self)
+            }
+
+            #[cfg(feature = "async")]
+            pub async fn spawn_selection(self) -> Result<Option<FileSelection>> {
+                FileDialog::spawn_run(self).await
+            }
+        }
+
+        impl FileDialog for $dialog {
+            fn owner(&self) -> &UnsafeWindowHandle {
+                &self.owner
+            }
+
+            fn requirements(&self) -> &FileRequirements {
+                &self.requirements
+            }
+
+            fn validator(&self) -> Option<&FileValidator> {
+                self.validator.as_ref()
+            }
+
+            fn memory_key(&self) -> Option<&str> {
+                self.memory_key.as_deref()
+            }
+
+            fn path_style(&self) -> &PathStyle {
+                &self.path_style
+            }
+
+            $(
+                fn $flag(&self) -> bool {
+                    self.$flag
+                }
+            )*
+
+            /// Opens the directory of the path, with its name filled in.
+            fn reopen_at(&mut self, path: &Path) {
+                self.location = path.parent().map(Path::to_path_buf);
+                self.filename = path.file_name().map(|x| x.to_string_lossy().into_owned());
+            }
+        }
+
+        impl DialogImpl for $dialog {
+            fn show(self) -> Result<Self::Output> {
+                let $selection = FileDialog::run(self)?;
+                Ok($output)
+            }
+
+            #[cfg(feature = "async")]
+            async fn spawn(self) -> Result<Self::Output> {
+                let $selection = FileDialog::spawn_run(self).await?;
+                Ok($output)
+            }
+        }
+    };
+}
+
+file_dialog!(OpenSingleFile, add_to_recent, |x| x
+    .and_then(FileSelection::into_path));
+file_dialog!(OpenMultipleFile, add_to_recent, dedup_paths, |x| x
+    .map(|x| x.paths)
+    .unwrap_or_default());
+file_dialog!(OpenSingleDir, |x| x.and_then(FileSelection::into_path));
+file_dialog!(OpenMultipleDir, dedup_paths, |x| x
+    .map(|x| x.paths)
+    .unwrap_or_default());
+file_dialog!(OpenSingleFileOrDir, |x| x
+    .and_then(FileOrDir::from_selection));
+file_dialog!(SaveSingleFile, add_to_recent, |x| x
+    .and_then(FileSelection::into_path));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty directory for a test, removing what an earlier run left there.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("native-dialog-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn checks_existence() {
+        let dir = scratch_dir("existence");
+        let file = dir.join("file.txt");
+        let missing = dir.join("missing.txt");
+        std::fs::write(&file, "").unwrap();
+
+        let must_exist = FileRequirements {
+            must_exist: true,
+            ..Default::default()
+        };
+        assert_eq!(must_exist.check(std::slice::from_ref(&file)), None);
+        assert_eq!(
+            must_exist.check(&[file.clone(), missing.clone()]),
+            Some(String::from("\"missing.txt\" does not exist."))
+        );
+
+        let must_not_exist = FileRequirements {
+            must_not_exist: true,
+            ..Default::default()
+        };
+        assert_eq!(must_not_exist.check(&[missing]), None);
+        assert_eq!(
+            must_not_exist.check(&[file]),
+            Some(String::from("\"file.txt\" already exists."))
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn checks_kinds_of_paths() {
+        let dir = scratch_dir("kinds");
+        let file = dir.join("file.txt");
+        std::fs::write(&file, "").unwrap();
+
+        let requirements = FileRequirements {
+            must_be_readable: true,
+            directory_must_be_empty: true,
+            ..Default::default()
+        };
+
+        // Files pass the check for empty directories, while the directory holding one fails it
+        assert_eq!(requirements.check(std::slice::from_ref(&file)), None);
+        let name = display_name(&dir);
+        assert_eq!(
+            requirements.check(std::slice::from_ref(&dir)),
+            Some(format!("\"{name}\" is not empty."))
+        );
+
+        std::fs::remove_file(file).unwrap();
+        assert_eq!(requirements.check(std::slice::from_ref(&dir)), None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn checks_writable_parents() {
+        let dir = scratch_dir("writable");
+        let requirements = FileRequirements {
+            parent_must_be_writable: true,
+            ..Default::default()
+        };
+
+        assert_eq!(requirements.check(&[dir.join("new.txt")]), None);
+        assert_eq!(
+            requirements.check(&[dir.join("missing/new.txt")]),
+            Some(String::from("Cannot create files in \"missing\"."))
+        );
+
+        // Permission bits do not stop root, so only other users see the directory as read-only
+        #[cfg(unix)]
+        if unsafe { libc::geteuid() } != 0 {
+            use std::os::unix::fs::PermissionsExt;
+
+            let locked = dir.join("locked");
+            std::fs::create_dir(&locked).unwrap();
+            std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o555)).unwrap();
+            assert_eq!(
+                requirements.check(&[locked.join("new.txt")]),
+                Some(String::from("Cannot create files in \"locked\"."))
+            );
+            std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -1,9 +1,15 @@
 use formatx::formatx;
 
+use crate::utils::resolve_mime_globs;
+
+#[cfg(unix)]
+use crate::utils::{fold_case, glob_match};
+
 /// A list of file filters.
 #[derive(Debug, Clone, Default)]
 pub struct FileFiltersBag {
     pub items: Vec<FileFilter>,
+    pub default: Option<usize>,
 }
 
 impl FileFiltersBag {
@@ -18,6 +24,135 @@ impl FileFiltersBag {
         }
     }
 
+    pub fn add_patterns<T, U, V>(&mut self, name: T, patterns: V)
+    where
+        T: ToString,
+        U: ToString,
+        V: AsRef<[U]>,
+    {
+        if let Some(filter) = FileFilter::from_patterns(name, patterns) {
+            self.items.push(filter);
+        }
+    }
+
+    pub fn add_mime_filter<T, U, V>(&mut self, name: T, mime_types: V)
+    where
+        T: ToString,
+        U: ToString,
+        V: AsRef<[U]>,
+    {
+        if let Some(filter) = FileFilter::from_mime_types(name, mime_types) {
+            self.items.push(filter);
+        }
+    }
+
+    pub fn add_preset(&mut self, preset: Preset) {
+        self.items.push(preset.to_filter());
+    }
+
+    /// Parses Qt style filters, such as `Images (*.png *.jpg);;Text (*.txt)`. Characters in the
+    /// names can be escaped with backslashes.
+    pub fn parse_qt(filters: &str) -> Self {
+        let items = split_unescaped(filters, ";;")
+            .into_iter()
+            .filter_map(|entry| {
+                let (name, types) = split_types(entry.trim());
+                FileFilter::from_types(unescape(name), types.unwrap_or(name))
+            })
+            .collect();
+
+        FileFiltersBag {
+            items,
+            default: None,
+        }
+    }
+
+    /// Parses GTK style filters, one per line, such as `Images (*.png *.jpg) | *.png *.jpg`, as
+    /// zenity and yad take them. The types in the names are optional.
+    pub fn parse_gtk(filters: &str) -> Self {
+        let items = filters
+            .lines()
+            .filter_map(|line| {
+                let mut parts = split_unescaped(line, "|").into_iter();
+                let (name, types) = split_types(parts.next()?.trim());
+                let types = parts.next().or(types).unwrap_or(name);
+                FileFilter::from_types(unescape(name), types)
+            })
+            .collect();
+
+        FileFiltersBag {
+            items,
+            default: None,
+        }
+    }
+
+    /// Formats the filters as a Qt style string, as kdialog takes them.
+    pub fn to_qt_string(&self) -> String {
+        let filters: Vec<String> = self
+            .items
+            .iter()
+            .map(|filter| filter.escaped().format("{name} ({types})", "*{ext}", " "))
+            .collect();
+
+        filters.join(";;")
+    }
+
+    /// Formats the filters as a GTK style string, one per line, as zenity and yad take them.
+    pub fn to_gtk_string(&self) -> String {
+        let filters: Vec<String> = self
+            .items
+            .iter()
+            .map(|filter| {
+                filter
+                    .escaped()
+                    .format("{name} ({types}) | {types}", "*{ext}", " ")
+            })
+            .collect();
+
+        filters.join("\n")
+    }
+
+    /// Sets whether all the filters match filenames case-sensitively.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        for filter in &mut self.items {
+            filter.case_sensitive = case_sensitive;
+        }
+        self
+    }
+
+    /// Returns the index of the filter that is selected when the dialog opens.
+    pub fn default_index(&self) -> usize {
+        self.default
+            .filter(|index| *index < self.items.len())
+            .unwrap_or(0)
+    }
+
+    /// Iterates over the filters with the default one moved to the front, for implementations that
+    /// always select the first filter.
+    pub fn default_first(&self) -> impl Iterator<Item = &FileFilter> {
+        let default = self.default_index();
+        let rest = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| *i != default);
+        self.items
+            .get(default)
+            .into_iter()
+            .chain(rest.map(|(_, filter)| filter))
+    }
+
+    /// Returns the index of the filter accepting the path, preferring the default one.
+    #[cfg(unix)]
+    pub fn position(&self, path: impl AsRef<std::path::Path>) -> Option<usize> {
+        let default = self.default_index();
+        if self.items.get(default)?.accepts(&path) {
+            return Some(default);
+        }
+
+        self.items.iter().position(|filter| filter.accepts(&path))
+    }
+
     #[cfg(unix)]
     pub fn accepts(&self, path: impl AsRef<std::path::Path>) -> bool {
         if self.items.is_empty() {
@@ -34,11 +169,18 @@ impl FileFiltersBag {
     }
 }
 
-/// A file type filter, consisting of its name and a set of file extensions.
+/// A file type filter, consisting of its name and a set of file extensions or glob patterns.
 #[derive(Debug, Clone)]
 pub struct FileFilter {
     pub name: String,
     pub extensions: Vec<String>,
+    /// Glob patterns matched against filenames, such as `Makefile` or `*.tar.*`. These are passed
+    /// to the implementations unmodified.
+    pub patterns: Vec<String>,
+    /// MIME types that the patterns were resolved from, if any.
+    pub mime_types: Vec<String>,
+    /// Whether filenames must match the case of the extensions and patterns. Defaults to `false`.
+    pub case_sensitive: bool,
 }
 
 impl FileFilter {
@@ -48,40 +190,174 @@ impl FileFilter {
         U: ToString,
         V: AsRef<[U]>,
     {
-        let extensions = extensions.as_ref();
+        let extensions: Vec<String> = extensions
+            .as_ref()
+            .iter()
+            .map(ToString::to_string)
+            .filter(|ext| !ext.is_empty())
+            .map(|mut ext| {
+                if !ext.starts_with('.') {
+                    ext.insert(0, '.');
+                }
+                ext
+            })
+            .collect();
+
         if extensions.is_empty() {
             return None;
         }
 
         Some(FileFilter {
             name: name.to_string(),
-            extensions: extensions
-                .iter()
-                .map(ToString::to_string)
-                .filter(|ext| !ext.is_empty())
-                .map(|mut ext| {
-                    if !ext.starts_with('.') {
-                        ext.insert(0, '.');
-                    }
-                    ext
-                })
-                .collect(),
+            extensions,
+            patterns: vec![],
+            mime_types: vec![],
+            case_sensitive: false,
+        })
+    }
+
+    pub fn from_patterns<T, U, V>(name: T, patterns: V) -> Option<Self>
+    where
+        T: ToString,
+        U: ToString,
+        V: AsRef<[U]>,
+    {
+        let patterns: Vec<String> = patterns
+            .as_ref()
+            .iter()
+            .map(ToString::to_string)
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+
+        if patterns.is_empty() {
+            return None;
+        }
+
+        Some(FileFilter {
+            name: name.to_string(),
+            extensions: vec![],
+            patterns,
+            mime_types: vec![],
+            case_sensitive: false,
         })
     }
 
+    /// Creates a filter of the glob patterns registered for the MIME types in the shared-mime-info
+    /// database. Wildcards such as `image/*` are supported. Returns `None` if none of the types
+    /// are known, or if there is no database.
+    pub fn from_mime_types<T, U, V>(name: T, mime_types: V) -> Option<Self>
+    where
+        T: ToString,
+        U: ToString,
+        V: AsRef<[U]>,
+    {
+        let mime_types: Vec<String> = mime_types
+            .as_ref()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        let mut filter = FileFilter::from_patterns(name, resolve_mime_globs(&mime_types))?;
+        filter.mime_types = mime_types;
+        Some(filter)
+    }
+
     #[cfg(unix)]
     pub fn accepts(&self, path: impl AsRef<std::path::Path>) -> bool {
         use std::os::unix::ffi::OsStrExt;
 
-        if let Some(name) = path.as_ref().file_name() {
-            for accepting in &self.extensions {
-                if name.as_bytes().ends_with(accepting.as_bytes()) {
-                    return true;
-                }
+        let Some(name) = path.as_ref().file_name() else {
+            return false;
+        };
+
+        if self.case_sensitive {
+            let accepts_ext = |ext: &String| name.as_bytes().ends_with(ext.as_bytes());
+            let accepts_pattern = |pattern: &String| glob_match(pattern, &name.to_string_lossy());
+
+            self.extensions.iter().any(accepts_ext) || self.patterns.iter().any(accepts_pattern)
+        } else {
+            let name = name.to_string_lossy().to_lowercase();
+            let accepts_ext = |ext: &String| name.ends_with(&ext.to_lowercase());
+            let accepts_pattern = |pattern: &String| glob_match(&pattern.to_lowercase(), &name);
+
+            self.extensions.iter().any(accepts_ext) || self.patterns.iter().any(accepts_pattern)
+        }
+    }
+
+    /// Returns the extension that a filename typed without one gets, with its leading dot. This is
+    /// the first extension, or else the suffix of the first pattern of the form `*.ext`, as MIME
+    /// types and most presets resolve to. Returns `None` if neither exists, such as for filters of
+    /// only `Makefile` or `*.tar.*`.
+    pub fn default_extension(&self) -> Option<&str> {
+        let from_pattern = || {
+            self.patterns.iter().find_map(|pattern| {
+                let ext = pattern.strip_prefix("*.")?;
+                let plain = !ext.is_empty() && !ext.contains(['*', '?', '[', '.']);
+                plain.then(|| &pattern[1..])
+            })
+        };
+
+        self.extensions
+            .first()
+            .map(String::as_str)
+            .or_else(from_pattern)
+    }
+
+    /// Returns the glob patterns of the filter, for implementations that match them
+    /// case-sensitively. Unless the filter is case-sensitive, letters are replaced by bracket
+    /// expressions of both cases.
+    #[cfg(unix)]
+    pub fn globs(&self) -> Vec<String> {
+        let globs = self
+            .extensions
+            .iter()
+            .map(|ext| format!("*{ext}"))
+            .chain(self.patterns.iter().cloned());
+
+        if self.case_sensitive {
+            globs.collect()
+        } else {
+            globs.map(|glob| fold_case(&glob)).collect()
+        }
+    }
+
+    /// Creates a filter of whitespace separated types. Types of the form `*.ext` become extensions,
+    /// and any other ones become patterns.
+    fn from_types(name: String, types: &str) -> Option<Self> {
+        let (extensions, patterns): (Vec<&str>, Vec<&str>) =
+            types.split_whitespace().partition(|x| {
+                x.strip_prefix("*.")
+                    .is_some_and(|ext| !ext.contains(['*', '?', '[']))
+            });
+
+        if extensions.is_empty() && patterns.is_empty() {
+            return None;
+        }
+
+        Some(FileFilter {
+            name,
+            extensions: extensions.iter().map(|x| x[1..].to_string()).collect(),
+            patterns: patterns.iter().map(ToString::to_string).collect(),
+            mime_types: vec![],
+            case_sensitive: false,
+        })
+    }
+
+    /// Returns a copy of the filter with the characters that have special meanings in filter
+    /// strings escaped in its name.
+    fn escaped(&self) -> Self {
+        let mut name = String::new();
+        for c in self.name.chars() {
+            if matches!(c, '\\' | '|' | '(' | ')') {
+                name.push('\\');
             }
+            name.push(c);
         }
 
-        false
+        FileFilter {
+            name,
+            ..self.clone()
+        }
     }
 
     pub fn format(&self, fmt_line: &str, fmt_type: &str, delimeter: &str) -> String {
@@ -89,8 +365,263 @@ impl FileFilter {
             .extensions
             .iter()
             .map(|ext| formatx!(fmt_type, ext = ext).unwrap())
+            .chain(self.patterns.iter().cloned())
             .collect();
 
         formatx!(fmt_line, name = &self.name, types = types.join(delimeter)).unwrap()
     }
 }
+
+/// Splits the string at the delimiters that are not escaped with backslashes.
+fn split_unescaped<'a>(s: &'a str, delimiter: &str) -> Vec<&'a str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut escaped = false;
+
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if i >= start && s[i..].starts_with(delimiter) {
+            parts.push(&s[start..i]);
+            start = i + delimiter.len();
+        }
+    }
+
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Splits `name (types)` at the last unescaped opening parenthesis. If the string doesn't end with
+/// types, the whole string is the name.
+fn split_types(entry: &str) -> (&str, Option<&str>) {
+    let Some(inner) = entry.strip_suffix(')') else {
+        return (entry, None);
+    };
+
+    match split_unescaped(inner, "(").as_slice() {
+        [.., last] if last.len() < inner.len() => {
+            let name = &inner[..inner.len() - last.len() - 1];
+            (name.trim_end(), Some(last))
+        }
+        _ => (entry, None),
+    }
+}
+
+fn unescape(s: &str) -> String {
+    let mut result = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.extend(chars.next()),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// A ready-made file type filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Preset {
+    Images,
+    RasterImages,
+    VectorImages,
+    Audio,
+    Video,
+    Archives,
+    Documents,
+    Spreadsheets,
+    SourceCode,
+    PlainText,
+    /// Accepts any file, with the `*` pattern.
+    AllFiles,
+}
+
+impl Preset {
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Images => "Images",
+            Preset::RasterImages => "Raster Images",
+            Preset::VectorImages => "Vector Images",
+            Preset::Audio => "Audio",
+            Preset::Video => "Video",
+            Preset::Archives => "Archives",
+            Preset::Documents => "Documents",
+            Preset::Spreadsheets => "Spreadsheets",
+            Preset::SourceCode => "Source Code",
+            Preset::PlainText => "Plain Text",
+            Preset::AllFiles => "All Files",
+        }
+    }
+
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Preset::Images => &[
+                "png", "jpg", "jpeg", "gif", "bmp", "webp", "tif", "tiff", "ico", "heic", "heif",
+                "avif", "svg", "svgz",
+            ],
+            Preset::RasterImages => &[
+                "png", "jpg", "jpeg", "gif", "bmp", "webp", "tif", "tiff", "ico", "heic", "heif",
+                "avif",
+            ],
+            Preset::VectorImages => &["svg", "svgz", "eps", "ai", "emf", "wmf"],
+            Preset::Audio => &[
+                "mp3", "wav", "flac", "ogg", "oga", "opus", "m4a", "aac", "wma", "aif", "aiff",
+            ],
+            Preset::Video => &[
+                "mp4", "m4v", "mkv", "webm", "mov", "avi", "wmv", "flv", "mpg", "mpeg", "ogv",
+                "3gp",
+            ],
+            Preset::Archives => &[
+                "zip", "tar", "gz", "tgz", "bz2", "tbz2", "xz", "txz", "zst", "7z", "rar",
+            ],
+            Preset::Documents => &["pdf", "doc", "docx", "odt", "rtf", "epub"],
+            Preset::Spreadsheets => &["xls", "xlsx", "ods", "csv", "tsv"],
+            Preset::SourceCode => &[
+                "c", "h", "cc", "cpp", "cxx", "hpp", "cs", "go", "java", "kt", "swift", "rs", "py",
+                "rb", "php", "js", "jsx", "ts", "tsx", "lua", "sh",
+            ],
+            Preset::PlainText => &["txt", "text", "log", "md"],
+            Preset::AllFiles => &[],
+        }
+    }
+
+    pub(crate) fn to_filter(self) -> FileFilter {
+        let filter = match self {
+            Preset::AllFiles => FileFilter::from_patterns(self.name(), ["*"]),
+            _ => FileFilter::new(self.name(), self.extensions()),
+        };
+
+        filter.expect("presets are never empty")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(bag: &FileFiltersBag) -> Vec<(&str, &[String], &[String])> {
+        bag.items
+            .iter()
+            .map(|x| (&*x.name, &*x.extensions, &*x.patterns))
+            .collect()
+    }
+
+    fn bag(filters: &[(&str, &[&str])]) -> FileFiltersBag {
+        let mut bag = FileFiltersBag::default();
+        for (name, types) in filters {
+            bag.items
+                .extend(FileFilter::from_types(name.to_string(), &types.join(" ")));
+        }
+        bag
+    }
+
+    #[test]
+    fn finds_default_extensions() {
+        let ext = |filter: Option<FileFilter>| filter?.default_extension().map(str::to_string);
+
+        assert_eq!(
+            ext(FileFilter::new("A", ["png", ".jpg"])).as_deref(),
+            Some(".png")
+        );
+        let patterns = |x: &[&str]| ext(FileFilter::from_patterns("A", x));
+        assert_eq!(patterns(&["Makefile", "*.md"]).as_deref(), Some(".md"));
+        assert_eq!(
+            patterns(&["*.tar.*", "*.tar.gz", "*.[ch]", "*.", "*"]),
+            None
+        );
+        assert_eq!(patterns(&["*éa", "*.é"]).as_deref(), Some(".é"));
+    }
+
+    #[test]
+    fn parses_qt_filters() {
+        let bag = FileFiltersBag::parse_qt("Images (*.png *.jpg);;Archives (*.tar.* Makefile)");
+        assert_eq!(
+            summary(&bag),
+            [
+                ("Images", &[".png".into(), ".jpg".into()][..], &[][..]),
+                ("Archives", &[], &["*.tar.*".into(), "Makefile".into()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_gtk_filters() {
+        let bag = FileFiltersBag::parse_gtk("Images (*.png) | *.png *.gif\nText | *.txt\n*.md");
+        assert_eq!(
+            summary(&bag),
+            [
+                ("Images", &[".png".into(), ".gif".into()][..], &[][..]),
+                ("Text", &[".txt".into()], &[]),
+                ("*.md", &[".md".into()], &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_escaped_names() {
+        let qt = FileFiltersBag::parse_qt(r"A \(b\) \;\; c (*.x);;Pipe \| \\ (*.y)");
+        assert_eq!(qt.items[0].name, "A (b) ;; c");
+        assert_eq!(qt.items[1].name, r"Pipe | \");
+
+        let gtk = FileFiltersBag::parse_gtk(r"A \| b \(c\) | *.x");
+        assert_eq!(gtk.items[0].name, "A | b (c)");
+        assert_eq!(gtk.items[0].extensions, [".x"]);
+    }
+
+    #[test]
+    fn round_trips_qt_filters() {
+        let bag = bag(&[
+            ("Images (raster)", &["*.png", "*.jpg"]),
+            (r"Odd | name \ here", &["*.tar.*", "Makefile"]),
+        ]);
+        let string = bag.to_qt_string();
+        assert_eq!(
+            string,
+            r"Images \(raster\) (*.png *.jpg);;Odd \| name \\ here (*.tar.* Makefile)"
+        );
+        assert_eq!(summary(&FileFiltersBag::parse_qt(&string)), summary(&bag));
+    }
+
+    #[test]
+    fn round_trips_gtk_filters() {
+        let bag = bag(&[
+            ("Images (raster)", &["*.png", "*.jpg"]),
+            (r"Odd | name \ here", &["*.tar.*", "Makefile"]),
+        ]);
+        let string = bag.to_gtk_string();
+        assert_eq!(
+            string,
+            concat!(
+                r"Images \(raster\) (*.png *.jpg) | *.png *.jpg",
+                "\n",
+                r"Odd \| name \\ here (*.tar.* Makefile) | *.tar.* Makefile",
+            )
+        );
+        assert_eq!(summary(&FileFiltersBag::parse_gtk(&string)), summary(&bag));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn accepts_any_case_unless_asked() {
+        let mut filter = FileFilter::new("Images", ["png"]).unwrap();
+        let mut patterns = FileFilter::from_patterns("Docs", ["Makefile", "*.straße"]).unwrap();
+
+        assert!(filter.accepts("a/photo.png"));
+        assert!(filter.accepts("a/PHOTO.PNG"));
+        assert!(!filter.accepts("a/photo.png.bak"));
+        assert!(patterns.accepts("makefile"));
+        assert!(patterns.accepts("MAP.STRAßE"));
+        assert_eq!(patterns.globs()[1], "*.[sS][tT][rR][aA]ß[eE]");
+
+        filter.case_sensitive = true;
+        patterns.case_sensitive = true;
+
+        assert!(filter.accepts("a/photo.png"));
+        assert!(!filter.accepts("a/PHOTO.PNG"));
+        assert!(patterns.accepts("Makefile"));
+        assert!(!patterns.accepts("makefile"));
+        assert_eq!(patterns.globs(), ["Makefile", "*.straße"]);
+    }
+}
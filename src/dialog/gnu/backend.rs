@@ -1,10 +1,12 @@
-use std::process::Command;
+use std::io::Write;
+use std::process::{Child, ChildStdin, Command, Stdio};
 
 use ascii::AsAsciiStr;
 
 use super::version::Version;
 use crate::{Error, Result};
 
+#[derive(Clone, Copy)]
 pub enum BackendKind {
     KDialog,
     Zenity,
@@ -18,6 +20,11 @@ pub struct Backend {
 
 impl Backend {
     pub fn new() -> Result<Backend> {
+        Self::new_supporting(|_| true)
+    }
+
+    /// Picks an implementation like `new`, but skips the ones that cannot show the dialog.
+    pub fn new_supporting(supports: impl Fn(&BackendKind) -> bool) -> Result<Backend> {
         let has_display = match std::env::var("DISPLAY") {
             Ok(display) => !display.is_empty(),
             _ => false,
@@ -29,12 +36,22 @@ impl Backend {
             _ => [Self::new_yad, Self::new_kdialog, Self::new_zenity],
         };
 
+        let mut found = false;
         for candidate in candidates {
             if let Some(command) = candidate() {
-                return Ok(command);
+                if supports(&command.kind) {
+                    return Ok(command);
+                }
+                found = true;
             }
         }
 
+        if found {
+            return Err(Error::Unsupported(
+                "no installed implementation supports the dialog",
+            ));
+        }
+
         Err(Error::MissingDep)
     }
 
@@ -81,25 +98,87 @@ impl Backend {
         }
     }
 
-    pub fn exec(mut self) -> Result<Option<Vec<u8>>> {
+    pub fn exec(self) -> Result<Option<Vec<u8>>> {
+        let (code, stdout) = self.exec_with_code()?;
+        Ok((code == 0).then_some(stdout))
+    }
+
+    /// Like `exec`, but returns the exit code along with the output instead of dropping the output
+    /// when the exit code is not 0.
+    pub fn exec_with_code(mut self) -> Result<(i32, Vec<u8>)> {
         let program = self.command.get_program().to_os_string();
 
         let output = self.command.output()?;
         match output.status.code() {
-            Some(0) => Ok(Some(output.stdout)),
-            Some(_) => Ok(None),
+            Some(code) => Ok((code, output.stdout)),
             None => Err(Error::Killed(program)),
         }
     }
 
+    /// Starts the process without waiting for it, keeping its stdin open for further input.
+    pub fn launch(mut self) -> Result<Child> {
+        self.command.stdin(Stdio::piped());
+        Ok(self.command.spawn()?)
+    }
+
     #[cfg(feature = "async")]
     pub async fn spawn(self) -> Result<Option<Vec<u8>>> {
+        let (code, stdout) = self.spawn_with_code().await?;
+        Ok((code == 0).then_some(stdout))
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn spawn_with_code(self) -> Result<(i32, Vec<u8>)> {
         let (send, recv) = futures_channel::oneshot::channel();
 
         std::thread::spawn(move || {
-            let _ = send.send(self.exec());
+            let _ = send.send(self.exec_with_code());
         });
 
-        recv.await.unwrap_or(Ok(None))
+        recv.await.unwrap_or(Ok((1, vec![])))
+    }
+}
+
+/// A running process which reads commands line by line from its stdin.
+#[derive(Debug)]
+pub struct Pipe {
+    child: Child,
+    stdin: Option<ChildStdin>,
+}
+
+impl Pipe {
+    /// Launches the process, discarding its stdout.
+    pub fn launch(mut backend: Backend) -> Result<Self> {
+        backend.command.stdout(Stdio::null());
+        Ok(Self::new(backend.launch()?))
+    }
+
+    pub fn new(mut child: Child) -> Self {
+        let stdin = child.stdin.take();
+        Self { child, stdin }
+    }
+
+    pub fn send(&mut self, line: &str) -> Result {
+        if let Some(stdin) = &mut self.stdin {
+            writeln!(stdin, "{line}")?;
+            stdin.flush()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_closed(&mut self) -> Result<bool> {
+        Ok(self.child.try_wait()?.is_some())
+    }
+
+    pub fn close(&mut self) -> Result {
+        drop(self.stdin.take());
+
+        if self.child.try_wait()?.is_none() {
+            self.child.kill()?;
+            self.child.wait()?;
+        }
+
+        Ok(())
     }
 }
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
@@ -5,9 +6,10 @@ use std::path::{Path, PathBuf};
 use super::backend::{Backend, BackendKind};
 use crate::Result;
 use crate::dialog::{
-    DialogImpl, FileFiltersBag, OpenMultipleFile, OpenSingleDir, OpenSingleFile, SaveSingleFile,
+    FileChoice, FileFilter, FileFiltersBag, FileSelection, OpenMultipleDir, OpenMultipleFile,
+    OpenSingleDir, OpenSingleFile, OpenSingleFileOrDir, OverwritePolicy, SaveSingleFile,
+    SelectImpl, Verdict, infer_selection,
 };
-use crate::utils::resolve_tilde;
 
 impl OpenSingleFile {
     fn create(&self) -> Result<Backend> {
@@ -19,26 +21,42 @@ impl OpenSingleFile {
             multiple: false,
             dir: false,
             save: false,
+            confirm_overwrite: false,
             title: &self.title,
             owner: unsafe { self.owner.as_x11() },
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label.as_deref(),
+            preview: self.preview,
+            large_preview: self.large_preview,
+            choices: !self.choices.is_empty(),
         };
 
         init_backend(params)
     }
 }
 
-impl DialogImpl for OpenSingleFile {
-    fn show(self) -> Result<Self::Output> {
+impl SelectImpl for OpenSingleFile {
+    fn select(self) -> Result<Option<FileSelection>> {
         let backend = self.create()?;
         let output = backend.exec()?;
-        Ok(output.map(parse_output))
+        Ok(infer_selection(output.map(parse_output), &self.filters))
     }
 
     #[cfg(feature = "async")]
-    async fn spawn(self) -> Result<Self::Output> {
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
         let backend = self.create()?;
         let output = backend.spawn().await?;
-        Ok(output.map(parse_output))
+        Ok(infer_selection(output.map(parse_output), &self.filters))
+    }
+
+    fn finish(self, selection: FileSelection) -> Result<Option<FileSelection>> {
+        choose(selection, &self.choices, &self.title)
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn_finish(self, selection: FileSelection) -> Result<Option<FileSelection>> {
+        spawn_choose(selection, &self.choices, &self.title).await
     }
 }
 
@@ -52,46 +70,48 @@ impl OpenMultipleFile {
             multiple: true,
             dir: false,
             save: false,
+            confirm_overwrite: false,
             title: &self.title,
             owner: unsafe { self.owner.as_x11() },
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label.as_deref(),
+            preview: self.preview,
+            large_preview: self.large_preview,
+            choices: !self.choices.is_empty(),
         };
 
         init_backend(params)
     }
 }
 
-impl DialogImpl for OpenMultipleFile {
-    fn show(self) -> Result<Self::Output> {
+impl SelectImpl for OpenMultipleFile {
+    fn select(self) -> Result<Option<FileSelection>> {
         let backend = self.create()?;
         let output = backend.exec()?;
-
-        let paths = match output {
-            Some(output) => output
-                .split(|c| *c == b'\n')
-                .filter(|c| !c.is_empty())
-                .map(parse_output)
-                .collect(),
-            None => vec![],
-        };
-
-        Ok(paths)
+        Ok(infer_selection(
+            parse_multiple_output(output),
+            &self.filters,
+        ))
     }
 
     #[cfg(feature = "async")]
-    async fn spawn(self) -> Result<Self::Output> {
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
         let backend = self.create()?;
         let output = backend.spawn().await?;
+        Ok(infer_selection(
+            parse_multiple_output(output),
+            &self.filters,
+        ))
+    }
 
-        let paths = match output {
-            Some(output) => output
-                .split(|c| *c == b'\n')
-                .filter(|c| !c.is_empty())
-                .map(parse_output)
-                .collect(),
-            None => vec![],
-        };
+    fn finish(self, selection: FileSelection) -> Result<Option<FileSelection>> {
+        choose(selection, &self.choices, &self.title)
+    }
 
-        Ok(paths)
+    #[cfg(feature = "async")]
+    async fn spawn_finish(self, selection: FileSelection) -> Result<Option<FileSelection>> {
+        spawn_choose(selection, &self.choices, &self.title).await
     }
 }
 
@@ -105,84 +125,157 @@ impl OpenSingleDir {
             multiple: false,
             dir: true,
             save: false,
+            confirm_overwrite: false,
             title: &self.title,
             owner: unsafe { self.owner.as_x11() },
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label.as_deref(),
+            preview: false,
+            large_preview: false,
+            choices: false,
         };
 
         init_backend(params)
     }
 }
 
-impl DialogImpl for OpenSingleDir {
-    fn show(self) -> Result<Self::Output> {
+impl SelectImpl for OpenSingleDir {
+    fn select(self) -> Result<Option<FileSelection>> {
         let backend = self.create()?;
         let output = backend.exec()?;
-        Ok(output.map(parse_output))
+        Ok(FileSelection::from_dirs(
+            output.map(parse_output).into_iter().collect(),
+        ))
     }
 
     #[cfg(feature = "async")]
-    async fn spawn(self) -> Result<Self::Output> {
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
         let backend = self.create()?;
         let output = backend.spawn().await?;
-        Ok(output.map(parse_output))
+        Ok(FileSelection::from_dirs(
+            output.map(parse_output).into_iter().collect(),
+        ))
     }
 }
 
-impl SaveSingleFile {
-    fn create(&self, target: &Option<PathBuf>) -> Result<Backend> {
+impl OpenMultipleDir {
+    fn create(&self) -> Result<Backend> {
+        let target = get_target(&self.location, &self.filename);
+
         let params = BackendParams {
             target: target.as_deref(),
-            filters: &self.filters,
-            multiple: false,
-            dir: false,
-            save: true,
+            filters: &FileFiltersBag::default(),
+            multiple: true,
+            dir: true,
+            save: false,
+            confirm_overwrite: false,
             title: &self.title,
             owner: unsafe { self.owner.as_x11() },
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label.as_deref(),
+            preview: false,
+            large_preview: false,
+            choices: false,
         };
 
         init_backend(params)
     }
+}
 
-    fn warn(&self, path: &Path) -> Result<Backend> {
-        let message = match path.extension() {
-            None => String::from("Unrecognized file type. Please try again."),
-            Some(ext) => {
-                let ext = ext.to_string_lossy();
-                format!("Unrecognized file type: {ext}. Please try again.")
-            }
+impl SelectImpl for OpenMultipleDir {
+    fn select(self) -> Result<Option<FileSelection>> {
+        let backend = self.create()?;
+        let output = backend.exec()?;
+        Ok(FileSelection::from_dirs(parse_multiple_output(output)))
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        let backend = self.create()?;
+        let output = backend.spawn().await?;
+        Ok(FileSelection::from_dirs(parse_multiple_output(output)))
+    }
+}
+
+impl OpenSingleFileOrDir {
+    fn create(&self, dir: bool) -> Result<Backend> {
+        let target = get_target(&self.location, &self.filename);
+        let no_filters = FileFiltersBag::default();
+
+        let params = BackendParams {
+            target: target.as_deref(),
+            filters: if dir { &no_filters } else { &self.filters },
+            multiple: false,
+            dir,
+            save: false,
+            confirm_overwrite: false,
+            title: &self.title,
+            owner: unsafe { self.owner.as_x11() },
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label.as_deref(),
+            preview: self.preview,
+            large_preview: self.large_preview,
+            choices: false,
         };
 
+        init_backend(params)
+    }
+
+    /// None of the implementations can select both files and directories in the same dialog, so
+    /// users are asked which one they want first.
+    fn ask(&self) -> Result<Backend> {
+        let question = "Do you want to open a file or a folder?";
+
         let mut backend = Backend::new()?;
         match backend.kind {
             BackendKind::KDialog => {
+                if let Some(owner) = unsafe { self.owner.as_x11() } {
+                    backend.command.arg(format!("--attach=0x{:x}", owner));
+                }
+
                 backend.command.args([
-                    "--msgbox",
-                    &message,
+                    "--yesnocancel",
+                    question,
+                    "--yes-label",
+                    "File",
+                    "--no-label",
+                    "Folder",
                     "--title",
-                    "Warning",
-                    "--icon=dialog-warning",
+                    &self.title,
                 ]);
             }
             BackendKind::Zenity => {
                 backend.command.args([
                     "--width=400",
-                    "--warning",
+                    "--question",
+                    "--switch",
+                    "--extra-button",
+                    "File",
+                    "--extra-button",
+                    "Folder",
                     "--title",
-                    "Warning",
+                    &self.title,
                     "--text",
-                    &message,
+                    question,
                 ]);
             }
             BackendKind::Yad => {
                 backend.command.args([
                     "--button",
-                    "Ok:0",
+                    "File:0",
+                    "--button",
+                    "Folder:2",
+                    "--button",
+                    "Cancel:1",
                     "--image",
-                    "dialog-warning",
+                    "dialog-question",
                     "--title",
-                    "Warning",
+                    &self.title,
                     "--text",
-                    &message,
+                    question,
                 ]);
             }
         };
@@ -191,58 +284,348 @@ impl SaveSingleFile {
     }
 }
 
-impl DialogImpl for SaveSingleFile {
-    fn show(self) -> Result<Self::Output> {
-        let mut target = get_target(&self.location, &self.filename);
+/// Returns whether users want a directory, or `None` if they cancelled.
+fn parse_answer(kind: BackendKind, (code, stdout): (i32, Vec<u8>)) -> Option<bool> {
+    match (kind, code) {
+        // Extra buttons print their labels and exit with 1
+        (BackendKind::Zenity, _) => match stdout.trim_ascii() {
+            b"File" => Some(false),
+            b"Folder" => Some(true),
+            _ => None,
+        },
+        (_, 0) => Some(false),
+        (BackendKind::KDialog, 1) | (BackendKind::Yad, 2) => Some(true),
+        _ => None,
+    }
+}
 
-        loop {
-            let backend = self.create(&target)?;
-            let output = backend.exec()?;
+impl SelectImpl for OpenSingleFileOrDir {
+    fn select(self) -> Result<Option<FileSelection>> {
+        let chooser = self.ask()?;
+        let kind = chooser.kind;
+        let Some(dir) = parse_answer(kind, chooser.exec_with_code()?) else {
+            return Ok(None);
+        };
 
-            let Some(path) = output.map(parse_output) else {
-                break Ok(None);
-            };
+        let backend = self.create(dir)?;
+        let output = backend.exec()?;
+        let filters = if dir {
+            &FileFiltersBag::default()
+        } else {
+            &self.filters
+        };
+        let selection = infer_selection(output.map(parse_output), filters);
+        Ok(selection.map(|x| FileSelection { is_dir: dir, ..x }))
+    }
 
-            if self.filters.accepts(&path) {
-                break Ok(Some(path));
-            }
+    #[cfg(feature = "async")]
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        let chooser = self.ask()?;
+        let kind = chooser.kind;
+        let Some(dir) = parse_answer(kind, chooser.spawn_with_code().await?) else {
+            return Ok(None);
+        };
 
-            self.warn(&path)?.exec()?;
+        let backend = self.create(dir)?;
+        let output = backend.spawn().await?;
+        let filters = if dir {
+            &FileFiltersBag::default()
+        } else {
+            &self.filters
+        };
+        let selection = infer_selection(output.map(parse_output), filters);
+        Ok(selection.map(|x| FileSelection { is_dir: dir, ..x }))
+    }
+}
 
-            target = Some(path);
+impl SaveSingleFile {
+    fn create(&self, target: &Option<PathBuf>) -> Result<Backend> {
+        let params = BackendParams {
+            target: target.as_deref(),
+            filters: &self.filters,
+            multiple: false,
+            dir: false,
+            save: true,
+            confirm_overwrite: self.overwrite_policy == OverwritePolicy::Prompt,
+            title: &self.title,
+            owner: unsafe { self.owner.as_x11() },
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label.as_deref(),
+            preview: false,
+            large_preview: false,
+            choices: !self.choices.is_empty(),
+        };
+
+        init_backend(params)
+    }
+
+    /// Appends the extension of the default filter if users typed a filename without one. None of
+    /// the implementations report the selected filter, and the filename matches none of them, so
+    /// the default one is the best guess.
+    fn complete(&self, path: &Path) -> Option<PathBuf> {
+        if !self.auto_extension || path.extension().is_some() {
+            return None;
         }
+
+        let filter = self.filters.items.get(self.filters.default_index())?;
+        let ext = filter.default_extension()?;
+
+        let mut name = path.file_name()?.to_os_string();
+        name.push(ext);
+        Some(path.with_file_name(name))
+    }
+
+    /// Creates the selection from the path that users chose, with the extension appended if
+    /// needed. The implementation only asked about replacing the filename that users typed.
+    fn selection(&self, path: Option<PathBuf>, prompted: bool) -> Option<FileSelection> {
+        let path = path?;
+        let (path, prompted) = match self.complete(&path) {
+            Some(path) => (path, false),
+            None => (path, prompted),
+        };
+
+        let selection = infer_selection(Some(path), &self.filters)?;
+        Some(FileSelection {
+            overwrite_confirmed: prompted,
+            ..selection
+        })
+    }
+}
+
+/// Whether the implementation asks users before overwriting a file, when it's told to do so.
+fn confirms_overwrite(backend: &Backend) -> bool {
+    match backend.kind {
+        BackendKind::KDialog => true,
+        // Older versions are given `--confirm-overwrite`, while newer ones always ask. If the
+        // version is unknown, neither can be relied on.
+        BackendKind::Zenity => backend.version().is_some(),
+        BackendKind::Yad => true,
+    }
+}
+
+impl SelectImpl for SaveSingleFile {
+    fn select(self) -> Result<Option<FileSelection>> {
+        let target = get_target(&self.location, &self.filename);
+        let backend = self.create(&target)?;
+        let prompted = confirms_overwrite(&backend);
+        let output = backend.exec()?;
+
+        Ok(self.selection(output.map(parse_output), prompted))
     }
 
     #[cfg(feature = "async")]
-    async fn spawn(self) -> Result<Self::Output> {
-        let mut target = get_target(&self.location, &self.filename);
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        let target = get_target(&self.location, &self.filename);
+        let backend = self.create(&target)?;
+        let prompted = confirms_overwrite(&backend);
+        let output = backend.spawn().await?;
 
-        loop {
-            let backend = self.create(&target)?;
-            let output = backend.spawn().await?;
+        Ok(self.selection(output.map(parse_output), prompted))
+    }
+
+    fn finish(self, selection: FileSelection) -> Result<Option<FileSelection>> {
+        choose(selection, &self.choices, &self.title)
+    }
 
-            let Some(path) = output.map(parse_output) else {
-                break Ok(None);
+    #[cfg(feature = "async")]
+    async fn spawn_finish(self, selection: FileSelection) -> Result<Option<FileSelection>> {
+        spawn_choose(selection, &self.choices, &self.title).await
+    }
+
+    /// None of the implementations check the file against the filters, and they may not ask
+    /// before overwriting it.
+    fn judge(&self, selection: &FileSelection) -> Verdict {
+        let Some(path) = selection.path() else {
+            return Verdict::Accept;
+        };
+
+        if !self.filters.accepts(path) {
+            let message = match path.extension() {
+                None => String::from("Unrecognized file type. Please try again."),
+                Some(ext) => {
+                    let ext = ext.to_string_lossy();
+                    format!("Unrecognized file type: {ext}. Please try again.")
+                }
             };
 
-            if self.filters.accepts(&path) {
-                break Ok(Some(path));
-            }
+            return Verdict::Reject(message);
+        }
 
-            self.warn(&path)?.spawn().await?;
+        if !path.exists() {
+            return Verdict::Accept;
+        }
 
-            target = Some(path);
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        match self.overwrite_policy {
+            OverwritePolicy::Prompt if !selection.overwrite_confirmed => Verdict::Confirm(format!(
+                "A file named \"{name}\" already exists. Do you want to replace it?"
+            )),
+            OverwritePolicy::Deny => Verdict::Reject(format!(
+                "A file named \"{name}\" already exists. Please choose another name."
+            )),
+            _ => Verdict::Accept,
         }
     }
 }
 
+/// Separates the values printed by forms.
+const FORM_SEPARATOR: &str = "\x1e";
+
+/// None of the implementations can show the extra choices in the file dialog, so they are asked
+/// in a form after the selection.
+fn ask_choices(choices: &[FileChoice], title: &str) -> Result<Backend> {
+    let mut backend = Backend::new_supporting(|kind| !matches!(kind, BackendKind::KDialog))?;
+    match backend.kind {
+        BackendKind::KDialog => unreachable!(),
+        BackendKind::Zenity => {
+            backend
+                .command
+                .args(["--forms", "--text", "Options", "--title", title]);
+            backend.command.args(["--separator", FORM_SEPARATOR]);
+
+            // Combos can't be given a default value, but they select the first one
+            for choice in choices {
+                let labels: Vec<&str> = if !choice.is_checkbox() {
+                    default_first(choice).collect()
+                } else if choice.default == "true" {
+                    vec!["Yes", "No"]
+                } else {
+                    vec!["No", "Yes"]
+                };
+
+                backend.command.arg("--add-combo");
+                backend.command.arg(&choice.label);
+                backend.command.arg("--combo-values");
+                backend.command.arg(labels.join("|"));
+            }
+        }
+        BackendKind::Yad => {
+            backend.command.args(["--form", "--title", title]);
+            backend.command.args(["--separator", FORM_SEPARATOR]);
+
+            let mut values = vec![];
+            for choice in choices {
+                backend.command.arg("--field");
+                if choice.is_checkbox() {
+                    backend.command.arg(format!("{}:CHK", choice.label));
+                    values.push(choice.default.to_uppercase());
+                } else {
+                    // The default option is marked with a caret
+                    backend.command.arg(format!("{}:CB", choice.label));
+                    let labels: Vec<String> = choice
+                        .options
+                        .iter()
+                        .map(|(id, label)| {
+                            if *id == choice.default {
+                                format!("^{label}")
+                            } else {
+                                label.to_string()
+                            }
+                        })
+                        .collect();
+                    values.push(labels.join("!"));
+                }
+            }
+
+            // Values of the fields follow the options
+            backend.command.args(values);
+        }
+    };
+
+    Ok(backend)
+}
+
+fn default_first(choice: &FileChoice) -> impl Iterator<Item = &str> {
+    let default = choice
+        .options
+        .iter()
+        .filter(|(id, _)| *id == choice.default);
+    let rest = choice
+        .options
+        .iter()
+        .filter(|(id, _)| *id != choice.default);
+    default.chain(rest).map(|(_, label)| label.as_str())
+}
+
+fn parse_choices(choices: &[FileChoice], output: &[u8]) -> HashMap<String, String> {
+    let output = String::from_utf8_lossy(output);
+    let values = output.trim_end_matches('\n').split(FORM_SEPARATOR);
+
+    choices
+        .iter()
+        .zip(values)
+        .map(|(choice, value)| {
+            let value = if choice.is_checkbox() {
+                match value {
+                    "TRUE" | "Yes" => Some("true"),
+                    "FALSE" | "No" => Some("false"),
+                    _ => None,
+                }
+            } else {
+                choice
+                    .options
+                    .iter()
+                    .find(|(_, label)| label == value)
+                    .map(|(id, _)| id.as_str())
+            };
+
+            let value = value.unwrap_or(&choice.default);
+            (choice.id.clone(), value.to_string())
+        })
+        .collect()
+}
+
+/// Asks for the extra choices, if any. Cancelling the form cancels the selection.
+fn choose(
+    mut selection: FileSelection,
+    choices: &[FileChoice],
+    title: &str,
+) -> Result<Option<FileSelection>> {
+    if !choices.is_empty() {
+        let Some(output) = ask_choices(choices, title)?.exec()? else {
+            return Ok(None);
+        };
+        selection.choices = parse_choices(choices, &output);
+    }
+
+    Ok(Some(selection))
+}
+
+#[cfg(feature = "async")]
+async fn spawn_choose(
+    mut selection: FileSelection,
+    choices: &[FileChoice],
+    title: &str,
+) -> Result<Option<FileSelection>> {
+    if !choices.is_empty() {
+        let Some(output) = ask_choices(choices, title)?.spawn().await? else {
+            return Ok(None);
+        };
+        selection.choices = parse_choices(choices, &output);
+    }
+
+    Ok(Some(selection))
+}
+
 fn parse_output(buf: impl AsRef<[u8]>) -> PathBuf {
     let bytes = buf.as_ref().trim_ascii();
     PathBuf::from(OsStr::from_bytes(bytes))
 }
 
+fn parse_multiple_output(output: Option<Vec<u8>>) -> Vec<PathBuf> {
+    match output {
+        Some(output) => output
+            .split(|c| *c == b'\n')
+            .filter(|c| !c.trim_ascii().is_empty())
+            .map(parse_output)
+            .collect(),
+        None => vec![],
+    }
+}
+
 fn get_target(location: &Option<PathBuf>, filename: &Option<String>) -> Option<PathBuf> {
-    let location = location.as_deref().and_then(resolve_tilde);
+    let location = location.as_deref();
     let filename = filename.as_deref();
 
     match location {
@@ -257,12 +640,19 @@ struct BackendParams<'a> {
     multiple: bool,
     dir: bool,
     save: bool,
+    confirm_overwrite: bool,
     title: &'a str,
     owner: Option<u64>,
+    show_hidden: Option<bool>,
+    can_create_directories: Option<bool>,
+    accept_label: Option<&'a str>,
+    preview: bool,
+    large_preview: bool,
+    choices: bool,
 }
 
 fn init_backend(params: BackendParams) -> Result<Backend> {
-    let mut backend = Backend::new()?;
+    let mut backend = Backend::new_supporting(|kind| supports(kind, &params))?;
     match backend.kind {
         BackendKind::KDialog => init_kdialog(&mut backend, params),
         BackendKind::Zenity => init_zenity(&mut backend, params),
@@ -272,6 +662,24 @@ fn init_backend(params: BackendParams) -> Result<Backend> {
     Ok(backend)
 }
 
+fn supports(kind: &BackendKind, params: &BackendParams) -> bool {
+    // None of the implementations can prevent users from creating directories
+    if params.can_create_directories == Some(false) {
+        return false;
+    }
+
+    match kind {
+        // kdialog cannot select multiple directories, nor be told to show hidden files, nor show
+        // a form for the extra choices
+        BackendKind::KDialog => {
+            !(params.dir && params.multiple) && params.show_hidden != Some(true) && !params.choices
+        }
+        // zenity has no previews, while kdialog always has them
+        BackendKind::Zenity => params.show_hidden != Some(true) && !params.preview,
+        BackendKind::Yad => true,
+    }
+}
+
 fn init_kdialog(backend: &mut Backend, params: BackendParams) {
     if let Some(owner) = params.owner {
         backend.command.arg(format!("--attach=0x{:x}", owner));
@@ -297,10 +705,11 @@ fn init_kdialog(backend: &mut Backend, params: BackendParams) {
     }
 
     if !params.filters.items.is_empty() {
+        // The first filter is selected at open. Unlike zenity and yad, kdialog matches the
+        // patterns case-insensitively, so they're passed as they are.
         let filters: Vec<String> = params
             .filters
-            .items
-            .iter()
+            .default_first()
             .map(|filter| filter.format("{name} ({types})", "*{ext}", " "))
             .collect();
 
@@ -308,12 +717,23 @@ fn init_kdialog(backend: &mut Backend, params: BackendParams) {
     }
 }
 
+/// zenity and yad match the patterns case-sensitively, so they're given the globs of the filter.
+fn format_filter(filter: &FileFilter) -> String {
+    let name = filter.format("{name} ({types})", "*{ext}", " ");
+    format!("{name} | {}", filter.globs().join(" "))
+}
+
 fn init_zenity(backend: &mut Backend, params: BackendParams) {
     backend.command.arg("--file-selection");
 
     backend.command.arg("--title");
     backend.command.arg(params.title);
 
+    if let Some(label) = params.accept_label {
+        backend.command.arg("--ok-label");
+        backend.command.arg(label);
+    }
+
     if params.dir {
         backend.command.arg("--directory");
     }
@@ -323,7 +743,7 @@ fn init_zenity(backend: &mut Backend, params: BackendParams) {
 
         // `--confirm-overwrite` was removed at zenity 3.91.0
         // https://gitlab.gnome.org/GNOME/zenity/-/issues/55
-        if matches!(backend.version(), Some(v) if v < (3, 91, 0)) {
+        if params.confirm_overwrite && matches!(backend.version(), Some(v) if v < (3, 91, 0)) {
             backend.command.arg("--confirm-overwrite");
         }
     };
@@ -338,8 +758,8 @@ fn init_zenity(backend: &mut Backend, params: BackendParams) {
     }
 
     if !params.filters.items.is_empty() {
-        for filter in &params.filters.items {
-            let formatted = filter.format("{name} ({types}) | {types}", "*{ext}", " ");
+        for filter in params.filters.default_first() {
+            let formatted = format_filter(filter);
             backend.command.arg("--file-filter");
             backend.command.arg(formatted);
         }
@@ -355,29 +775,139 @@ fn init_yad(backend: &mut Backend, params: BackendParams) {
     backend.command.arg("--title");
     backend.command.arg(params.title);
 
+    // yad has no `--ok-label`, so the buttons are replaced instead
+    if let Some(label) = params.accept_label {
+        backend.command.args(["--button", "Cancel:1", "--button"]);
+        backend.command.arg(format!("{label}:0"));
+    }
+
     if params.dir {
         backend.command.arg("--directory");
     }
 
     if params.save {
         backend.command.arg("--save");
-        backend.command.arg("--confirm-overwrite");
+
+        if params.confirm_overwrite {
+            backend.command.arg("--confirm-overwrite");
+        }
     };
 
+    if params.show_hidden == Some(true) {
+        backend.command.arg("--show-hidden");
+    }
+
     if params.multiple {
         backend.command.args(["--multiple", "--separator", "\n"]);
     }
 
+    if params.preview {
+        backend.command.arg("--add-preview");
+
+        if params.large_preview {
+            backend.command.arg("--large-preview");
+        }
+    }
+
     if let Some(path) = params.target {
         backend.command.arg("--filename");
         backend.command.arg(path);
     }
 
     if !params.filters.items.is_empty() {
-        for filter in &params.filters.items {
-            let formatted = filter.format("{name} ({types}) | {types}", "*{ext}", " ");
+        for filter in params.filters.default_first() {
+            let formatted = format_filter(filter);
             backend.command.arg("--file-filter");
             backend.command.arg(formatted);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DialogBuilder;
+
+    fn choices() -> Vec<FileChoice> {
+        let option = |id: &str, label: &str| (id.to_string(), label.to_string());
+        vec![
+            FileChoice {
+                id: String::from("encoding"),
+                label: String::from("Encoding"),
+                options: vec![option("utf8", "UTF-8"), option("latin1", "Latin-1")],
+                default: String::from("utf8"),
+            },
+            FileChoice {
+                id: String::from("readonly"),
+                label: String::from("Read-only"),
+                options: vec![],
+                default: String::from("false"),
+            },
+        ]
+    }
+
+    fn parse(output: &str) -> Vec<(String, String)> {
+        let mut values: Vec<_> = parse_choices(&choices(), output.as_bytes())
+            .into_iter()
+            .collect();
+        values.sort();
+        values
+    }
+
+    fn values(encoding: &str, readonly: &str) -> Vec<(String, String)> {
+        vec![
+            (String::from("encoding"), encoding.to_string()),
+            (String::from("readonly"), readonly.to_string()),
+        ]
+    }
+
+    #[test]
+    fn parses_zenity_choices() {
+        assert_eq!(parse("Latin-1\x1eYes\n"), values("latin1", "true"));
+        assert_eq!(parse("UTF-8\x1eNo\n"), values("utf8", "false"));
+    }
+
+    #[test]
+    fn parses_yad_choices() {
+        assert_eq!(parse("Latin-1\x1eTRUE\x1e\n"), values("latin1", "true"));
+        assert_eq!(parse("UTF-8\x1eFALSE\x1e\n"), values("utf8", "false"));
+    }
+
+    #[test]
+    fn falls_back_to_default_choices() {
+        assert_eq!(parse("Shift-JIS\x1emaybe\n"), values("utf8", "false"));
+        assert_eq!(parse("\x1e"), values("utf8", "false"));
+    }
+
+    #[test]
+    fn completes_extensions() {
+        let dialog = DialogBuilder::file()
+            .add_filter("Text", ["txt"])
+            .add_pattern_filter("Markdown", ["README", "*.md"])
+            .set_default_filter(1)
+            .set_auto_extension(true)
+            .save_single_file();
+
+        let complete = |path: &str| dialog.complete(Path::new(path));
+        assert_eq!(complete("/tmp/notes"), Some(PathBuf::from("/tmp/notes.md")));
+        assert_eq!(complete("/tmp/notes.txt"), None);
+        assert_eq!(
+            complete("/tmp/.hidden"),
+            Some(PathBuf::from("/tmp/.hidden.md"))
+        );
+    }
+
+    #[test]
+    fn completes_only_if_asked() {
+        let dialog = DialogBuilder::file()
+            .add_filter("Text", ["txt"])
+            .save_single_file();
+        assert_eq!(dialog.complete(Path::new("/tmp/notes")), None);
+
+        let dialog = DialogBuilder::file()
+            .add_pattern_filter("Archives", ["*.tar.*"])
+            .set_auto_extension(true)
+            .save_single_file();
+        assert_eq!(dialog.complete(Path::new("/tmp/notes")), None);
+    }
+}
@@ -0,0 +1,48 @@
+use std::process::Stdio;
+
+use super::backend::{Backend, BackendKind};
+use crate::Result;
+use crate::dialog::{DialogImpl, LogTail, LogWriter};
+
+impl LogTail {
+    fn create(&self) -> Result<Backend> {
+        // kdialog can only show the content of files
+        let mut backend = Backend::new_supporting(|kind| !matches!(kind, BackendKind::KDialog))?;
+        match backend.kind {
+            BackendKind::KDialog => unreachable!(),
+            BackendKind::Zenity => {
+                backend.command.args(["--text-info", "--auto-scroll"]);
+            }
+            BackendKind::Yad => {
+                backend
+                    .command
+                    .args(["--text-info", "--tail", "--button", "Close:0"]);
+            }
+        };
+
+        backend.command.args(["--width=600", "--height=400"]);
+
+        backend.command.arg("--title");
+        backend.command.arg(&self.title);
+
+        backend.command.stdout(Stdio::null());
+
+        Ok(backend)
+    }
+}
+
+impl DialogImpl for LogTail {
+    fn show(self) -> Result<Self::Output> {
+        let mut child = self.create()?.launch()?;
+
+        Ok(LogWriter {
+            stdin: child.stdin.take(),
+            child: Some(child),
+        })
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn(self) -> Result<Self::Output> {
+        self.show()
+    }
+}
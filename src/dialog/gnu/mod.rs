@@ -1,5 +1,8 @@
 mod file;
+mod log;
 mod message;
+mod progress;
+mod tray;
 
 mod backend;
 mod version;
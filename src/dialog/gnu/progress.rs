@@ -0,0 +1,404 @@
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::backend::{Backend, BackendKind, Pipe};
+use crate::dialog::{
+    BusyHandle, BusyIndicator, BusySignal, DialogImpl, MultiProgress, MultiProgressHandle,
+    ProgressSink,
+};
+use crate::{Error, Result};
+
+impl MultiProgress {
+    fn params(&self) -> BarParams<'_> {
+        BarParams {
+            title: &self.title,
+            text: &self.text,
+            pulsate: false,
+            cancellable: self.cancellable,
+            owner: unsafe { self.owner.as_x11() },
+        }
+    }
+}
+
+impl DialogImpl for MultiProgress {
+    fn show(self) -> Result<Self::Output> {
+        let backend = Backend::new_supporting(supports_progress)?;
+
+        let sink: Box<dyn ProgressSink> = match backend.kind {
+            BackendKind::KDialog => {
+                let bar = KDialogBar::start(backend, self.params())?;
+                Box::new(Overall::new(&self.bars, bar))
+            }
+            BackendKind::Zenity => {
+                let bar = PipeBar::start(backend, self.params())?;
+                Box::new(Overall::new(&self.bars, bar))
+            }
+            BackendKind::Yad => Box::new(YadBars::start(backend, self.params(), &self.bars)?),
+        };
+
+        Ok(MultiProgressHandle {
+            bars: self.bars,
+            sink,
+        })
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn(self) -> Result<Self::Output> {
+        self.show()
+    }
+}
+
+impl BusyIndicator {
+    fn params(&self) -> BarParams<'_> {
+        BarParams {
+            title: &self.title,
+            text: &self.text,
+            pulsate: true,
+            cancellable: self.cancellable,
+            owner: unsafe { self.owner.as_x11() },
+        }
+    }
+
+    /// Runs on a separate thread, showing the dialog from the end of the delay until the work
+    /// finishes.
+    fn watch(self, backend: Backend, signal: &BusySignal) {
+        if signal.wait_finished(self.delay) {
+            return;
+        }
+
+        // There's nobody to report the error to, so the work just goes on without the dialog
+        let Ok(mut bar) = start_single_bar(backend, self.params()) else {
+            return;
+        };
+
+        while !signal.wait_finished(BUSY_POLL_INTERVAL) {
+            if bar.is_closed().unwrap_or(true) {
+                if self.cancellable {
+                    signal.cancel();
+                }
+                return;
+            }
+        }
+
+        let _ = bar.close();
+    }
+}
+
+impl DialogImpl for BusyIndicator {
+    fn show(self) -> Result<Self::Output> {
+        let backend = Backend::new_supporting(supports_progress)?;
+
+        let signal = Arc::new(BusySignal::default());
+        let worker = std::thread::spawn({
+            let signal = signal.clone();
+            move || self.watch(backend, &signal)
+        });
+
+        Ok(BusyHandle {
+            signal,
+            worker: Some(worker),
+        })
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn(self) -> Result<Self::Output> {
+        self.show()
+    }
+}
+
+/// How often a busy indicator checks whether the user has closed it.
+const BUSY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct BarParams<'a> {
+    pub title: &'a str,
+    pub text: &'a str,
+    pub pulsate: bool,
+    pub cancellable: bool,
+    pub owner: Option<u64>,
+}
+
+fn start_single_bar(backend: Backend, params: BarParams) -> Result<Box<dyn SingleBar>> {
+    match backend.kind {
+        BackendKind::KDialog => Ok(Box::new(KDialogBar::start(backend, params)?)),
+        _ => Ok(Box::new(PipeBar::start(backend, params)?)),
+    }
+}
+
+/// kdialog progress bars can only be controlled through D-Bus.
+pub fn supports_progress(kind: &BackendKind) -> bool {
+    !matches!(kind, BackendKind::KDialog) || find_qdbus().is_some()
+}
+
+fn find_qdbus() -> Option<PathBuf> {
+    ["qdbus", "qdbus6", "qdbus-qt6", "qdbus-qt5"]
+        .into_iter()
+        .find_map(|name| which::which(name).ok())
+}
+
+fn percent(fraction: f64) -> u32 {
+    (fraction * 100.0).round() as u32
+}
+
+/// A progress dialog showing a single bar.
+pub trait SingleBar: Debug + Send {
+    fn set_percent(&mut self, percent: u32) -> Result;
+
+    fn set_label(&mut self, label: &str) -> Result;
+
+    fn is_closed(&mut self) -> Result<bool>;
+
+    fn close(&mut self) -> Result;
+}
+
+/// Shows multiple bars on a single bar, as the average of their progress.
+#[derive(Debug)]
+struct Overall<B> {
+    names: Vec<String>,
+    fractions: Vec<f64>,
+    current: Option<usize>,
+    bar: B,
+}
+
+impl<B: SingleBar> Overall<B> {
+    fn new(names: &[String], bar: B) -> Self {
+        Self {
+            names: names.to_vec(),
+            fractions: vec![0.0; names.len()],
+            current: None,
+            bar,
+        }
+    }
+}
+
+impl<B: SingleBar> ProgressSink for Overall<B> {
+    fn set(&mut self, index: usize, fraction: f64) -> Result {
+        self.fractions[index] = fraction;
+
+        if self.current != Some(index) {
+            self.bar.set_label(&self.names[index])?;
+            self.current = Some(index);
+        }
+
+        let total = self.fractions.iter().sum::<f64>() / self.fractions.len() as f64;
+        self.bar.set_percent(percent(total))
+    }
+
+    fn is_closed(&mut self) -> Result<bool> {
+        self.bar.is_closed()
+    }
+
+    fn close(&mut self) -> Result {
+        self.bar.close()
+    }
+}
+
+/// The progress dialog of zenity or yad, which share the same protocol on stdin.
+#[derive(Debug)]
+pub struct PipeBar {
+    pipe: Pipe,
+}
+
+impl PipeBar {
+    pub fn start(mut backend: Backend, params: BarParams) -> Result<Self> {
+        backend.command.args(["--progress", "--width=400"]);
+
+        if params.pulsate {
+            backend.command.arg("--pulsate");
+        } else {
+            backend.command.arg("--percentage=0");
+        }
+
+        match backend.kind {
+            BackendKind::Yad if params.cancellable => {
+                backend.command.args(["--button", "Cancel:1"]);
+            }
+            BackendKind::Yad => {
+                backend.command.arg("--no-buttons");
+            }
+            _ if !params.cancellable => {
+                backend.command.arg("--no-cancel");
+            }
+            _ => {}
+        }
+
+        backend.command.arg("--title");
+        backend.command.arg(params.title);
+
+        backend.command.arg("--text");
+        backend.command.arg(params.text);
+
+        let pipe = Pipe::launch(backend)?;
+        Ok(Self { pipe })
+    }
+}
+
+impl SingleBar for PipeBar {
+    fn set_percent(&mut self, percent: u32) -> Result {
+        self.pipe.send(&percent.to_string())
+    }
+
+    fn set_label(&mut self, label: &str) -> Result {
+        // Each line is a command, so the label must fit in one line
+        let label = label.replace('\n', " ");
+        self.pipe.send(&format!("# {label}"))
+    }
+
+    fn is_closed(&mut self) -> Result<bool> {
+        self.pipe.is_closed()
+    }
+
+    fn close(&mut self) -> Result {
+        self.pipe.close()
+    }
+}
+
+/// kdialog forks a progress dialog and prints its D-Bus reference, which is then used to update the
+/// dialog with `qdbus`.
+#[derive(Debug)]
+pub struct KDialogBar {
+    qdbus: PathBuf,
+    service: String,
+    object: String,
+    closed: bool,
+}
+
+impl KDialogBar {
+    pub fn start(mut backend: Backend, params: BarParams) -> Result<Self> {
+        let qdbus = find_qdbus().ok_or(Error::Unsupported("kdialog progress bar without qdbus"))?;
+
+        if let Some(owner) = params.owner {
+            backend.command.arg(format!("--attach=0x{:x}", owner));
+        }
+
+        // A progress bar without maximum value shows a busy indicator
+        backend.command.arg("--progressbar");
+        backend.command.arg(params.text);
+        backend
+            .command
+            .arg(if params.pulsate { "0" } else { "100" });
+
+        backend.command.arg("--title");
+        backend.command.arg(params.title);
+
+        let Some(output) = backend.exec()? else {
+            return Err(Error::Other(
+                "kdialog failed to start a progress bar".to_string(),
+            ));
+        };
+
+        let output = String::from_utf8(output)?;
+        let mut parts = output.split_whitespace();
+        let (Some(service), Some(object)) = (parts.next(), parts.next()) else {
+            return Err(Error::Other(format!(
+                "unexpected output of kdialog: {output}"
+            )));
+        };
+
+        let mut bar = Self {
+            qdbus,
+            service: service.to_string(),
+            object: object.to_string(),
+            closed: false,
+        };
+
+        if params.cancellable {
+            bar.call(&["showCancelButton", "true"])?;
+        }
+
+        Ok(bar)
+    }
+
+    /// Calls a method of the dialog. Returns `None` if the dialog is gone.
+    pub fn call(&mut self, args: &[&str]) -> Result<Option<String>> {
+        if self.closed {
+            return Ok(None);
+        }
+
+        let output = Command::new(&self.qdbus)
+            .arg(&self.service)
+            .arg(&self.object)
+            .args(args)
+            .stderr(Stdio::null())
+            .output()?;
+
+        if !output.status.success() {
+            self.closed = true;
+            return Ok(None);
+        }
+
+        Ok(Some(String::from_utf8(output.stdout)?))
+    }
+}
+
+impl SingleBar for KDialogBar {
+    fn set_percent(&mut self, percent: u32) -> Result {
+        self.call(&["Set", "", "value", &percent.to_string()])?;
+        Ok(())
+    }
+
+    fn set_label(&mut self, label: &str) -> Result {
+        self.call(&["setLabelText", label])?;
+        Ok(())
+    }
+
+    fn is_closed(&mut self) -> Result<bool> {
+        match self.call(&["wasCancelled"])? {
+            Some(cancelled) => Ok(cancelled.trim() == "true"),
+            None => Ok(true),
+        }
+    }
+
+    fn close(&mut self) -> Result {
+        self.call(&["close"])?;
+        self.closed = true;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct YadBars {
+    pipe: Pipe,
+}
+
+impl YadBars {
+    fn start(mut backend: Backend, params: BarParams, bars: &[String]) -> Result<Self> {
+        backend.command.args(["--multi-progress", "--width=400"]);
+
+        if !params.cancellable {
+            backend.command.arg("--no-buttons");
+        }
+
+        for bar in bars {
+            backend.command.arg("--bar");
+            backend.command.arg(format!("{bar}:NORM"));
+        }
+
+        backend.command.arg("--title");
+        backend.command.arg(params.title);
+
+        backend.command.arg("--text");
+        backend.command.arg(params.text);
+
+        let pipe = Pipe::launch(backend)?;
+        Ok(Self { pipe })
+    }
+}
+
+impl ProgressSink for YadBars {
+    fn set(&mut self, index: usize, fraction: f64) -> Result {
+        // Bars are numbered from 1
+        self.pipe
+            .send(&format!("{}:{}", index + 1, percent(fraction)))
+    }
+
+    fn is_closed(&mut self) -> Result<bool> {
+        self.pipe.is_closed()
+    }
+
+    fn close(&mut self) -> Result {
+        self.pipe.close()
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{ChildStdout, Stdio};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+
+use super::backend::{Backend, BackendKind, Pipe};
+use crate::dialog::{DialogImpl, TrayCallback, TrayHandle, TrayIcon, TrayMenuItem, TraySink};
+use crate::{Error, Result};
+
+/// Unusual characters that separate the menu items and their fields for yad, so that labels don't
+/// need to be escaped.
+const MENU_SEPARATOR: &str = "\x1e";
+const ITEM_SEPARATOR: &str = "\x1f";
+
+impl TrayIcon {
+    fn create(&self) -> Result<Backend> {
+        // Only yad supports menus and reports clicks
+        let interactive = !self.items.is_empty() || self.on_click.is_some();
+        let mut backend = Backend::new_supporting(|kind| match kind {
+            BackendKind::KDialog => false,
+            BackendKind::Zenity => !interactive,
+            BackendKind::Yad => true,
+        })?;
+
+        backend.command.args(["--notification", "--listen"]);
+
+        match backend.kind {
+            BackendKind::KDialog => unreachable!(),
+            BackendKind::Zenity => {
+                backend.command.arg("--window-icon");
+                backend.command.arg(&self.icon);
+
+                backend.command.arg("--text");
+                backend.command.arg(single_line(&self.tooltip));
+            }
+            BackendKind::Yad => {
+                backend.command.arg("--image");
+                backend.command.arg(&self.icon);
+
+                backend.command.arg("--text");
+                backend.command.arg(single_line(&self.tooltip));
+
+                backend.command.arg(format!("--separator={MENU_SEPARATOR}"));
+                backend
+                    .command
+                    .arg(format!("--item-separator={ITEM_SEPARATOR}"));
+
+                // Commands run by yad inherit its stdout, where the clicks are read from
+                backend.command.args(["--command", "echo click"]);
+            }
+        };
+
+        Ok(backend)
+    }
+}
+
+impl DialogImpl for TrayIcon {
+    fn show(self) -> Result<Self::Output> {
+        let mut backend = self.create()?;
+
+        let sink: Box<dyn TraySink> = match backend.kind {
+            BackendKind::KDialog => unreachable!(),
+            BackendKind::Zenity => Box::new(ZenityTray {
+                pipe: Pipe::launch(backend)?,
+            }),
+            BackendKind::Yad => {
+                backend.command.stdout(Stdio::piped());
+                let mut child = backend.launch()?;
+                let stdout = child.stdout.take();
+
+                let mut tray = YadTray {
+                    pipe: Pipe::new(child),
+                    callbacks: Arc::default(),
+                    next_id: 0,
+                    reader: None,
+                };
+
+                if let Some(stdout) = stdout {
+                    let callbacks = tray.callbacks.clone();
+                    let on_click = self.on_click;
+                    let reader =
+                        std::thread::spawn(move || read_clicks(stdout, callbacks, on_click));
+                    tray.reader = Some(reader);
+                }
+
+                tray.set_menu(self.items)?;
+
+                Box::new(tray)
+            }
+        };
+
+        Ok(TrayHandle { sink })
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn(self) -> Result<Self::Output> {
+        self.show()
+    }
+}
+
+/// Commands are read line by line, so the arguments must fit in one line.
+fn single_line(text: &str) -> String {
+    text.replace('\n', " ")
+}
+
+type Callbacks = Arc<Mutex<HashMap<u64, TrayCallback>>>;
+
+/// Runs on a separate thread until yad exits, invoking the callbacks of the clicked items.
+fn read_clicks(stdout: ChildStdout, callbacks: Callbacks, on_click: Option<TrayCallback>) {
+    for line in BufReader::new(stdout).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+
+        let callback = match line.trim().split_once(':') {
+            Some(("item", id)) => {
+                let callbacks = callbacks.lock().unwrap_or_else(PoisonError::into_inner);
+                id.parse().ok().and_then(|id| callbacks.get(&id).cloned())
+            }
+            _ if line.trim() == "click" => on_click.clone(),
+            _ => None,
+        };
+
+        if let Some(callback) = callback {
+            callback.call();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct YadTray {
+    pipe: Pipe,
+    callbacks: Callbacks,
+    next_id: u64,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl TraySink for YadTray {
+    fn set_icon(&mut self, icon: &str) -> Result {
+        self.pipe.send(&format!("icon:{}", single_line(icon)))
+    }
+
+    fn set_tooltip(&mut self, tooltip: &str) -> Result {
+        self.pipe.send(&format!("tooltip:{}", single_line(tooltip)))
+    }
+
+    fn set_visible(&mut self, visible: bool) -> Result {
+        self.pipe.send(&format!("visible:{visible}"))
+    }
+
+    fn set_menu(&mut self, items: Vec<TrayMenuItem>) -> Result {
+        let mut callbacks = HashMap::new();
+        let mut entries = Vec::new();
+
+        // Items get fresh ids, so that clicks on a stale menu are never mistaken for new items
+        for item in items {
+            let id = self.next_id;
+            self.next_id += 1;
+
+            let label = single_line(&item.label);
+            entries.push(format!("{label}{ITEM_SEPARATOR}echo item:{id}"));
+            callbacks.insert(id, item.callback);
+        }
+
+        *self
+            .callbacks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = callbacks;
+
+        self.pipe
+            .send(&format!("menu:{}", entries.join(MENU_SEPARATOR)))
+    }
+
+    fn close(&mut self) -> Result {
+        self.pipe.close()?;
+
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct ZenityTray {
+    pipe: Pipe,
+}
+
+impl TraySink for ZenityTray {
+    fn set_icon(&mut self, icon: &str) -> Result {
+        self.pipe.send(&format!("icon:{}", single_line(icon)))
+    }
+
+    fn set_tooltip(&mut self, tooltip: &str) -> Result {
+        self.pipe.send(&format!("tooltip:{}", single_line(tooltip)))
+    }
+
+    fn set_visible(&mut self, visible: bool) -> Result {
+        self.pipe.send(&format!("visible:{visible}"))
+    }
+
+    fn set_menu(&mut self, _items: Vec<TrayMenuItem>) -> Result {
+        Err(Error::Unsupported("tray menu"))
+    }
+
+    fn close(&mut self) -> Result {
+        self.pipe.close()
+    }
+}
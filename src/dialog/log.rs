@@ -0,0 +1,86 @@
+use std::io::Write;
+use std::process::{Child, ChildStdin};
+
+use super::Dialog;
+
+#[derive(Debug)]
+pub struct LogTail {
+    pub title: String,
+}
+
+impl Dialog for LogTail {
+    type Output = LogWriter;
+}
+
+impl LogTail {
+    super::dialog_delegate!();
+}
+
+/// Handle to a running log viewer. Everything written to it is appended to the dialog.
+///
+/// Dropping the handle ends the input but leaves the dialog open until the user closes it.
+#[derive(Debug)]
+pub struct LogWriter {
+    pub(crate) child: Option<Child>,
+    pub(crate) stdin: Option<ChildStdin>,
+}
+
+impl LogWriter {
+    /// Ends the input and blocks until the user closes the dialog.
+    pub fn wait(mut self) -> crate::Result {
+        drop(self.stdin.take());
+
+        if let Some(mut child) = self.child.take() {
+            child.wait()?;
+        }
+
+        Ok(())
+    }
+
+    /// Closes the dialog immediately.
+    pub fn close(mut self) -> crate::Result {
+        drop(self.stdin.take());
+
+        if let Some(mut child) = self.child.take() {
+            child.kill()?;
+            child.wait()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the user has already closed the dialog.
+    pub fn is_closed(&mut self) -> crate::Result<bool> {
+        match &mut self.child {
+            Some(child) => Ok(child.try_wait()?.is_some()),
+            None => Ok(true),
+        }
+    }
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &mut self.stdin {
+            Some(stdin) => stdin.write(buf),
+            None => Err(std::io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.stdin {
+            Some(stdin) => stdin.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        drop(self.stdin.take());
+
+        // Reap the process in the background so that it doesn't linger as a zombie
+        if let Some(mut child) = self.child.take() {
+            std::thread::spawn(move || child.wait());
+        }
+    }
+}
@@ -1,10 +1,17 @@
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+
 use dispatch2::run_on_main;
 use objc2::MainThreadMarker;
 use objc2::rc::Retained as Id;
 use objc2_app_kit::{NSOpenPanel, NSSavePanel};
 
 use crate::Result;
-use crate::dialog::{DialogImpl, OpenMultipleFile, OpenSingleDir, OpenSingleFile, SaveSingleFile};
+use crate::dialog::{
+    FileFiltersBag, FileSelection, OpenMultipleDir, OpenMultipleFile, OpenSingleDir,
+    OpenSingleFile, OpenSingleFileOrDir, SaveSingleFile, SelectImpl, ensure_no_choices,
+    infer_selection,
+};
 use crate::ffi::mac::{NSOpenPanelExt, NSSavePanelExt, OpenPanelDelegate, SavePanelDelegate};
 
 impl OpenSingleFile {
@@ -25,32 +32,50 @@ impl OpenSingleFile {
             panel.set_directory_url(location);
         }
 
+        if let Some(label) = &self.accept_label {
+            panel.set_prompt(label);
+        }
+
+        if let Some(show_hidden) = self.show_hidden {
+            panel.setShowsHiddenFiles(show_hidden);
+        }
+
+        if let Some(can_create_directories) = self.can_create_directories {
+            panel.setCanCreateDirectories(can_create_directories);
+        }
+
         panel
     }
 }
 
-impl DialogImpl for OpenSingleFile {
-    fn show(self) -> Result<Self::Output> {
+impl SelectImpl for OpenSingleFile {
+    fn select(self) -> Result<Option<FileSelection>> {
+        ensure_no_choices(&self.choices)?;
+
+        let owner = self.owner.clone();
         let res = run_on_main(|mtm| {
             let panel = self.create(mtm);
             let _delegate = OpenPanelDelegate::attach(&panel, &self.filters);
-            panel.show(self.owner)
+            panel.show(owner)
         });
 
-        Ok(res.into_iter().next())
+        Ok(infer_selection(res, &self.filters))
     }
 
     #[cfg(feature = "async")]
-    async fn spawn(self) -> Result<Self::Output> {
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        ensure_no_choices(&self.choices)?;
+
         use crate::ffi::mac::NSOpenPanelAsyncExt;
 
+        let owner = self.owner.clone();
         let res = run_on_main(|mtm| {
             let panel = self.create(mtm);
             let delegate = OpenPanelDelegate::attach(&panel, &self.filters);
-            panel.spawn(self.owner).retain(delegate)
+            panel.spawn(owner).retain(delegate)
         });
 
-        Ok(res.await.into_iter().next())
+        Ok(infer_selection(res.await, &self.filters))
     }
 }
 
@@ -72,32 +97,50 @@ impl OpenMultipleFile {
             panel.set_directory_url(location);
         }
 
+        if let Some(label) = &self.accept_label {
+            panel.set_prompt(label);
+        }
+
+        if let Some(show_hidden) = self.show_hidden {
+            panel.setShowsHiddenFiles(show_hidden);
+        }
+
+        if let Some(can_create_directories) = self.can_create_directories {
+            panel.setCanCreateDirectories(can_create_directories);
+        }
+
         panel
     }
 }
 
-impl DialogImpl for OpenMultipleFile {
-    fn show(self) -> Result<Self::Output> {
+impl SelectImpl for OpenMultipleFile {
+    fn select(self) -> Result<Option<FileSelection>> {
+        ensure_no_choices(&self.choices)?;
+
+        let owner = self.owner.clone();
         let res = run_on_main(|mtm| {
             let panel = self.create(mtm);
             let _delegate = OpenPanelDelegate::attach(&panel, &self.filters);
-            panel.show(self.owner)
+            panel.show(owner)
         });
 
-        Ok(res)
+        Ok(infer_selection(res, &self.filters))
     }
 
     #[cfg(feature = "async")]
-    async fn spawn(self) -> Result<Self::Output> {
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        ensure_no_choices(&self.choices)?;
+
         use crate::ffi::mac::NSOpenPanelAsyncExt;
 
+        let owner = self.owner.clone();
         let res = run_on_main(|mtm| {
             let panel = self.create(mtm);
             let delegate = OpenPanelDelegate::attach(&panel, &self.filters);
-            panel.spawn(self.owner).retain(delegate)
+            panel.spawn(owner).retain(delegate)
         });
 
-        Ok(res.await)
+        Ok(infer_selection(res.await, &self.filters))
     }
 }
 
@@ -118,22 +161,90 @@ impl OpenSingleDir {
             panel.set_directory_url(location);
         }
 
+        if let Some(label) = &self.accept_label {
+            panel.set_prompt(label);
+        }
+
+        if let Some(show_hidden) = self.show_hidden {
+            panel.setShowsHiddenFiles(show_hidden);
+        }
+
+        if let Some(can_create_directories) = self.can_create_directories {
+            panel.setCanCreateDirectories(can_create_directories);
+        }
+
+        panel
+    }
+}
+
+impl SelectImpl for OpenSingleDir {
+    fn select(self) -> Result<Option<FileSelection>> {
+        let res = run_on_main(|mtm| {
+            let panel = self.create(mtm);
+            panel.show(self.owner)
+        });
+
+        Ok(FileSelection::from_dirs(res))
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        use crate::ffi::mac::NSOpenPanelAsyncExt;
+
+        let res = run_on_main(|mtm| {
+            let panel = self.create(mtm);
+            panel.spawn(self.owner)
+        });
+
+        Ok(FileSelection::from_dirs(res.await))
+    }
+}
+
+impl OpenMultipleDir {
+    fn create(&self, mtm: MainThreadMarker) -> Id<NSOpenPanel> {
+        let panel = NSOpenPanel::openPanel(mtm);
+
+        panel.set_title(&self.title);
+        panel.setCanChooseFiles(false);
+        panel.setCanChooseDirectories(true);
+        panel.setAllowsMultipleSelection(true);
+
+        if let Some(filename) = &self.filename {
+            panel.set_name_field_string_value(filename);
+        }
+
+        if let Some(location) = &self.location {
+            panel.set_directory_url(location);
+        }
+
+        if let Some(label) = &self.accept_label {
+            panel.set_prompt(label);
+        }
+
+        if let Some(show_hidden) = self.show_hidden {
+            panel.setShowsHiddenFiles(show_hidden);
+        }
+
+        if let Some(can_create_directories) = self.can_create_directories {
+            panel.setCanCreateDirectories(can_create_directories);
+        }
+
         panel
     }
 }
 
-impl DialogImpl for OpenSingleDir {
-    fn show(self) -> Result<Self::Output> {
+impl SelectImpl for OpenMultipleDir {
+    fn select(self) -> Result<Option<FileSelection>> {
         let res = run_on_main(|mtm| {
             let panel = self.create(mtm);
             panel.show(self.owner)
         });
 
-        Ok(res.into_iter().next())
+        Ok(FileSelection::from_dirs(res))
     }
 
     #[cfg(feature = "async")]
-    async fn spawn(self) -> Result<Self::Output> {
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
         use crate::ffi::mac::NSOpenPanelAsyncExt;
 
         let res = run_on_main(|mtm| {
@@ -141,10 +252,81 @@ impl DialogImpl for OpenSingleDir {
             panel.spawn(self.owner)
         });
 
-        Ok(res.await.into_iter().next())
+        Ok(FileSelection::from_dirs(res.await))
+    }
+}
+
+impl OpenSingleFileOrDir {
+    fn create(&self, mtm: MainThreadMarker) -> Id<NSOpenPanel> {
+        let panel = NSOpenPanel::openPanel(mtm);
+
+        panel.set_title(&self.title);
+        panel.setCanChooseFiles(true);
+        panel.setCanChooseDirectories(true);
+        panel.setAllowsMultipleSelection(false);
+
+        if let Some(filename) = &self.filename {
+            panel.set_name_field_string_value(filename);
+        }
+
+        if let Some(location) = &self.location {
+            panel.set_directory_url(location);
+        }
+
+        if let Some(label) = &self.accept_label {
+            panel.set_prompt(label);
+        }
+
+        if let Some(show_hidden) = self.show_hidden {
+            panel.setShowsHiddenFiles(show_hidden);
+        }
+
+        if let Some(can_create_directories) = self.can_create_directories {
+            panel.setCanCreateDirectories(can_create_directories);
+        }
+
+        panel
     }
 }
 
+impl SelectImpl for OpenSingleFileOrDir {
+    fn select(self) -> Result<Option<FileSelection>> {
+        let owner = self.owner.clone();
+        let res = run_on_main(|mtm| {
+            let panel = self.create(mtm);
+            let _delegate = OpenPanelDelegate::attach(&panel, &self.filters);
+            panel.show(owner)
+        });
+
+        Ok(tag_selection(res, &self.filters))
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        use crate::ffi::mac::NSOpenPanelAsyncExt;
+
+        let owner = self.owner.clone();
+        let res = run_on_main(|mtm| {
+            let panel = self.create(mtm);
+            let delegate = OpenPanelDelegate::attach(&panel, &self.filters);
+            panel.spawn(owner).retain(delegate)
+        });
+
+        Ok(tag_selection(res.await, &self.filters))
+    }
+}
+
+/// The panel lets users choose either, so whether they chose a directory is looked up while the
+/// path is still as the panel reported it.
+fn tag_selection(paths: Vec<PathBuf>, filters: &FileFiltersBag) -> Option<FileSelection> {
+    let is_dir = paths.first().is_some_and(|path| path.is_dir());
+    let selection = infer_selection(paths, filters)?;
+    Some(FileSelection {
+        is_dir,
+        ..selection
+    })
+}
+
 impl SaveSingleFile {
     fn create(&self, mtm: MainThreadMarker) -> Id<NSSavePanel> {
         let panel = NSSavePanel::savePanel(mtm);
@@ -161,31 +343,63 @@ impl SaveSingleFile {
             panel.set_directory_url(location);
         }
 
+        if let Some(label) = &self.accept_label {
+            panel.set_prompt(label);
+        }
+
+        if let Some(show_hidden) = self.show_hidden {
+            panel.setShowsHiddenFiles(show_hidden);
+        }
+
+        if let Some(can_create_directories) = self.can_create_directories {
+            panel.setCanCreateDirectories(can_create_directories);
+        }
+
         panel
     }
 }
 
-impl DialogImpl for SaveSingleFile {
-    fn show(self) -> Result<Self::Output> {
-        let res = run_on_main(|mtm| {
+impl SelectImpl for SaveSingleFile {
+    fn select(self) -> Result<Option<FileSelection>> {
+        ensure_no_choices(&self.choices)?;
+
+        let owner = self.owner.clone();
+        let (res, selected) = run_on_main(|mtm| {
             let panel = self.create(mtm);
-            let _delegate = SavePanelDelegate::attach(&panel, &self.filters);
-            panel.show(self.owner)
+            let delegate = SavePanelDelegate::attach(&panel, &self.filters, self.overwrite_policy);
+            (panel.show(owner), delegate.selected_index())
         });
 
-        Ok(res)
+        let filter_index =
+            (!self.filters.items.is_empty()).then(|| selected.load(Ordering::Relaxed));
+        Ok(res.map(|path| FileSelection {
+            paths: vec![path],
+            filter_index,
+            ..Default::default()
+        }))
     }
 
     #[cfg(feature = "async")]
-    async fn spawn(self) -> Result<Self::Output> {
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        ensure_no_choices(&self.choices)?;
+
         use crate::ffi::mac::NSSavePanelAsyncExt;
 
-        let res = run_on_main(|mtm| {
+        let owner = self.owner.clone();
+        let (res, selected) = run_on_main(|mtm| {
             let panel = self.create(mtm);
-            let delegate = SavePanelDelegate::attach(&panel, &self.filters);
-            panel.spawn(self.owner).retain(delegate)
+            let delegate = SavePanelDelegate::attach(&panel, &self.filters, self.overwrite_policy);
+            let selected = delegate.selected_index();
+            (panel.spawn(owner).retain(delegate), selected)
         });
 
-        Ok(res.await)
+        let res = res.await;
+        let filter_index =
+            (!self.filters.items.is_empty()).then(|| selected.load(Ordering::Relaxed));
+        Ok(res.map(|path| FileSelection {
+            paths: vec![path],
+            filter_index,
+            ..Default::default()
+        }))
     }
 }
@@ -4,9 +4,21 @@ pub use file::*;
 pub mod filter;
 pub use filter::*;
 
+pub mod recent;
+pub use recent::*;
+
 pub mod message;
 pub use message::*;
 
+pub mod log;
+pub use log::*;
+
+pub mod progress;
+pub use progress::*;
+
+pub mod tray;
+pub use tray::*;
+
 pub trait Dialog {
     type Output;
 }
@@ -46,3 +58,12 @@ mod gnu;
 
 #[cfg(target_os = "windows")]
 mod win;
+
+// Dialogs that only the GNU implementations can show
+#[cfg(not(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+)))]
+mod unsupported;
@@ -0,0 +1,202 @@
+use std::fmt::Debug;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::task::Waker;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use super::Dialog;
+use crate::utils::UnsafeWindowHandle;
+use crate::{Error, Result};
+
+#[derive(Debug)]
+pub struct MultiProgress {
+    pub title: String,
+    pub text: String,
+    pub bars: Vec<String>,
+    pub cancellable: bool,
+    pub owner: UnsafeWindowHandle,
+}
+
+impl Dialog for MultiProgress {
+    type Output = MultiProgressHandle;
+}
+
+impl MultiProgress {
+    super::dialog_delegate!();
+}
+
+/// Handle to a running progress dialog with named bars. Dropping the handle closes the dialog.
+#[derive(Debug)]
+pub struct MultiProgressHandle {
+    pub(crate) bars: Vec<String>,
+    pub(crate) sink: Box<dyn ProgressSink>,
+}
+
+impl MultiProgressHandle {
+    /// Sets the progress of the named bar. The fraction is clamped into `0.0..=1.0`.
+    pub fn set(&mut self, bar: &str, fraction: f64) -> Result {
+        let Some(index) = self.bars.iter().position(|x| x == bar) else {
+            return Err(Error::Other(format!("unknown progress bar: {bar}")));
+        };
+
+        self.sink.set(index, fraction.clamp(0.0, 1.0))
+    }
+
+    /// Returns whether the dialog has been closed or cancelled by the user.
+    pub fn is_closed(&mut self) -> Result<bool> {
+        self.sink.is_closed()
+    }
+
+    /// Closes the dialog.
+    pub fn close(mut self) -> Result {
+        self.sink.close()
+    }
+}
+
+impl Drop for MultiProgressHandle {
+    fn drop(&mut self) {
+        let _ = self.sink.close();
+    }
+}
+
+/// The implementation-specific part of a running progress dialog.
+pub(crate) trait ProgressSink: Debug + Send {
+    fn set(&mut self, index: usize, fraction: f64) -> Result;
+
+    fn is_closed(&mut self) -> Result<bool>;
+
+    /// Closes the dialog. Must be a no-op if the dialog is already closed.
+    fn close(&mut self) -> Result;
+}
+
+#[derive(Debug)]
+pub struct BusyIndicator {
+    pub title: String,
+    pub text: String,
+    pub cancellable: bool,
+    pub delay: Duration,
+    pub owner: UnsafeWindowHandle,
+}
+
+impl Dialog for BusyIndicator {
+    type Output = BusyHandle;
+}
+
+impl BusyIndicator {
+    super::dialog_delegate!();
+
+    /// Runs the closure while showing the dialog, and closes the dialog once the closure returns.
+    /// The closure can poll the handle to find out whether the user has cancelled the dialog.
+    pub fn run<T>(self, f: impl FnOnce(&BusyHandle) -> T) -> Result<T> {
+        let handle = self.show()?;
+        let output = f(&handle);
+        handle.close();
+        Ok(output)
+    }
+
+    /// Awaits the future while showing the dialog, and closes the dialog once the future completes.
+    /// Returns `None` if the user cancelled the dialog first, in which case the future is dropped.
+    #[cfg(feature = "async")]
+    pub async fn run_async<T>(self, future: impl Future<Output = T>) -> Result<Option<T>> {
+        use futures_lite::FutureExt;
+
+        let handle = self.spawn().await?;
+
+        let work = async { Some(future.await) };
+        let cancelled = async {
+            handle.cancelled().await;
+            None
+        };
+        let output = work.or(cancelled).await;
+
+        handle.close();
+        Ok(output)
+    }
+}
+
+/// Handle to a busy indicator. Dropping the handle closes the dialog, or prevents it from showing
+/// up if the delay hasn't elapsed yet.
+#[derive(Debug)]
+pub struct BusyHandle {
+    pub(crate) signal: Arc<BusySignal>,
+    pub(crate) worker: Option<JoinHandle<()>>,
+}
+
+impl BusyHandle {
+    /// Returns whether the user has cancelled the dialog.
+    pub fn is_cancelled(&self) -> bool {
+        self.signal.state().cancelled
+    }
+
+    /// Resolves once the user cancels the dialog. Never resolves if that doesn't happen.
+    #[cfg(feature = "async")]
+    pub async fn cancelled(&self) {
+        std::future::poll_fn(|cx| {
+            let mut state = self.signal.state();
+            if state.cancelled {
+                return std::task::Poll::Ready(());
+            }
+
+            match &mut state.waker {
+                Some(waker) if waker.will_wake(cx.waker()) => {}
+                waker => *waker = Some(cx.waker().clone()),
+            }
+            std::task::Poll::Pending
+        })
+        .await
+    }
+
+    /// Closes the dialog.
+    pub fn close(self) {}
+}
+
+impl Drop for BusyHandle {
+    fn drop(&mut self) {
+        self.signal.state().finished = true;
+        self.signal.changed.notify_all();
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// State shared by a [`BusyHandle`] and the thread that manages the dialog.
+#[derive(Debug, Default)]
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+pub(crate) struct BusySignal {
+    state: Mutex<BusyState>,
+    changed: Condvar,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct BusyState {
+    finished: bool,
+    cancelled: bool,
+    waker: Option<Waker>,
+}
+
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+impl BusySignal {
+    fn state(&self) -> MutexGuard<'_, BusyState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Blocks until the work finishes or the timeout elapses. Returns whether the work finished.
+    pub fn wait_finished(&self, timeout: Duration) -> bool {
+        let state = self.state();
+        let result = self
+            .changed
+            .wait_timeout_while(state, timeout, |x| !x.finished);
+        let (state, _) = result.unwrap_or_else(PoisonError::into_inner);
+        state.finished
+    }
+
+    pub fn cancel(&self) {
+        let mut state = self.state();
+        state.cancelled = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::Result;
+
+/// A file in the recent files list of the desktop, as read by GTK and KDE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentFile {
+    pub path: PathBuf,
+    pub mime_type: Option<String>,
+    pub modified: Option<SystemTime>,
+}
+
+/// Lists the files this application added to the recent files list, most recently used first.
+/// Files that no longer exist are skipped. The list is only kept on Linux and BSD.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn recent_files() -> Result<Vec<RecentFile>> {
+    use std::cmp::Reverse;
+
+    use crate::utils::{app_name, read_bookmarks};
+
+    let (Some(file), Some(app)) = (recent_files_xbel(), app_name()) else {
+        return Ok(vec![]);
+    };
+
+    let xbel = match std::fs::read_to_string(file) {
+        Ok(xbel) => xbel,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut files: Vec<_> = read_bookmarks(&xbel)
+        .into_iter()
+        .filter(|x| x.apps.contains(&app) && x.path.exists())
+        .map(|x| RecentFile {
+            path: x.path,
+            mime_type: x.mime_type,
+            modified: x.modified,
+        })
+        .collect();
+
+    files.sort_by_key(|x| Reverse(x.modified));
+    Ok(files)
+}
+
+/// Lists the files this application added to the recent files list, most recently used first.
+/// Files that no longer exist are skipped. The list is only kept on Linux and BSD.
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+pub fn recent_files() -> Result<Vec<RecentFile>> {
+    Err(crate::Error::Unsupported("recent files"))
+}
+
+/// Adds files to the recent files list on behalf of this application.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub(crate) fn add_recent_files(paths: &[PathBuf]) -> Result {
+    use crate::utils::{add_bookmark, app_name, guess_mime_type};
+
+    let (Some(file), Some(app)) = (recent_files_xbel(), app_name()) else {
+        return Ok(());
+    };
+
+    let mut xbel = match std::fs::read_to_string(&file) {
+        Ok(xbel) => xbel,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    // Writing over a list that is cut short would drop the bookmarks of other applications
+    if !xbel.trim().is_empty() && !xbel.contains("</xbel>") {
+        let error = "the recent files list is incomplete";
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, error).into());
+    }
+
+    let now = SystemTime::now();
+    for path in paths {
+        let mime_type = guess_mime_type(path);
+        let mime_type = mime_type.as_deref().unwrap_or("application/octet-stream");
+        if let Some(updated) = add_bookmark(&xbel, path, mime_type, &app, now) {
+            xbel = updated;
+        }
+    }
+
+    // Other applications may read the file at any time, so it is replaced at once
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temp = file.with_extension(format!("xbel.{}.tmp", std::process::id()));
+    std::fs::write(&temp, xbel)?;
+    std::fs::rename(temp, file)?;
+
+    Ok(())
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+pub(crate) fn add_recent_files(_paths: &[PathBuf]) -> Result {
+    Err(crate::Error::Unsupported("recent files"))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn recent_files_xbel() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("recently-used.xbel"))
+}
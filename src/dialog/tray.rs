@@ -0,0 +1,111 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use super::Dialog;
+use crate::Result;
+
+#[derive(Debug)]
+pub struct TrayIcon {
+    pub icon: String,
+    pub tooltip: String,
+    pub items: Vec<TrayMenuItem>,
+    pub on_click: Option<TrayCallback>,
+}
+
+impl Dialog for TrayIcon {
+    type Output = TrayHandle;
+}
+
+impl TrayIcon {
+    super::dialog_delegate!();
+}
+
+/// A callback invoked when the user interacts with a tray icon. Callbacks are invoked on a
+/// background thread.
+#[derive(Clone)]
+pub struct TrayCallback(Arc<dyn Fn() + Send + Sync>);
+
+impl TrayCallback {
+    pub fn new(callback: impl Fn() + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    pub fn call(&self) {
+        (self.0)()
+    }
+}
+
+impl Debug for TrayCallback {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TrayCallback")
+    }
+}
+
+/// An item in the menu of a tray icon.
+#[derive(Debug, Clone)]
+pub struct TrayMenuItem {
+    pub label: String,
+    pub callback: TrayCallback,
+}
+
+impl TrayMenuItem {
+    pub fn new(label: impl ToString, callback: impl Fn() + Send + Sync + 'static) -> Self {
+        Self {
+            label: label.to_string(),
+            callback: TrayCallback::new(callback),
+        }
+    }
+}
+
+/// Handle to a tray icon. Dropping the handle removes the icon.
+#[derive(Debug)]
+pub struct TrayHandle {
+    pub(crate) sink: Box<dyn TraySink>,
+}
+
+impl TrayHandle {
+    /// Changes the icon, which is the name of an icon in the icon theme or a path to an image.
+    pub fn set_icon(&mut self, icon: &str) -> Result {
+        self.sink.set_icon(icon)
+    }
+
+    /// Changes the text shown when hovering over the icon.
+    pub fn set_tooltip(&mut self, tooltip: &str) -> Result {
+        self.sink.set_tooltip(tooltip)
+    }
+
+    /// Shows or hides the icon without removing it.
+    pub fn set_visible(&mut self, visible: bool) -> Result {
+        self.sink.set_visible(visible)
+    }
+
+    /// Replaces the items in the menu of the icon.
+    pub fn set_menu(&mut self, items: Vec<TrayMenuItem>) -> Result {
+        self.sink.set_menu(items)
+    }
+
+    /// Removes the icon.
+    pub fn close(mut self) -> Result {
+        self.sink.close()
+    }
+}
+
+impl Drop for TrayHandle {
+    fn drop(&mut self) {
+        let _ = self.sink.close();
+    }
+}
+
+/// The implementation-specific part of a running tray icon.
+pub(crate) trait TraySink: Debug + Send {
+    fn set_icon(&mut self, icon: &str) -> Result;
+
+    fn set_tooltip(&mut self, tooltip: &str) -> Result;
+
+    fn set_visible(&mut self, visible: bool) -> Result;
+
+    fn set_menu(&mut self, items: Vec<TrayMenuItem>) -> Result;
+
+    /// Removes the icon. Must be a no-op if the icon is already removed.
+    fn close(&mut self) -> Result;
+}
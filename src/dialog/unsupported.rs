@@ -0,0 +1,46 @@
+use crate::dialog::{BusyIndicator, DialogImpl, LogTail, MultiProgress, TrayIcon};
+use crate::{Error, Result};
+
+impl DialogImpl for LogTail {
+    fn show(self) -> Result<Self::Output> {
+        Err(Error::Unsupported("log viewer"))
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn(self) -> Result<Self::Output> {
+        self.show()
+    }
+}
+
+impl DialogImpl for MultiProgress {
+    fn show(self) -> Result<Self::Output> {
+        Err(Error::Unsupported("progress dialog"))
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn(self) -> Result<Self::Output> {
+        self.show()
+    }
+}
+
+impl DialogImpl for BusyIndicator {
+    fn show(self) -> Result<Self::Output> {
+        Err(Error::Unsupported("progress dialog"))
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn(self) -> Result<Self::Output> {
+        self.show()
+    }
+}
+
+impl DialogImpl for TrayIcon {
+    fn show(self) -> Result<Self::Output> {
+        Err(Error::Unsupported("tray icon"))
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn(self) -> Result<Self::Output> {
+        self.show()
+    }
+}
@@ -1,15 +1,16 @@
 use std::path::Path;
 
 use wfd::{
-    DialogError, DialogParams, FOS_ALLOWMULTISELECT, FOS_FILEMUSTEXIST, FOS_NOREADONLYRETURN,
-    FOS_OVERWRITEPROMPT, FOS_PATHMUSTEXIST, FOS_PICKFOLDERS, FOS_STRICTFILETYPES, OpenDialogResult,
-    SaveDialogResult,
+    DialogError, DialogParams, FOS_ALLOWMULTISELECT, FOS_FILEMUSTEXIST, FOS_FORCESHOWHIDDEN,
+    FOS_NOREADONLYRETURN, FOS_OVERWRITEPROMPT, FOS_PATHMUSTEXIST, FOS_PICKFOLDERS,
+    FOS_STRICTFILETYPES, OpenDialogResult, SaveDialogResult,
 };
 
 use crate::dialog::{
-    DialogImpl, FileFilter, OpenMultipleFile, OpenSingleDir, OpenSingleFile, SaveSingleFile,
+    FileFilter, FileSelection, OpenMultipleDir, OpenMultipleFile, OpenSingleDir, OpenSingleFile,
+    OpenSingleFileOrDir, OverwritePolicy, SaveSingleFile, SelectImpl, Verdict, ensure_no_choices,
 };
-use crate::utils::{UnsafeWindowHandle, resolve_tilde};
+use crate::utils::UnsafeWindowHandle;
 use crate::{Error, Result};
 
 impl OpenSingleFile {
@@ -19,23 +20,34 @@ impl OpenSingleFile {
             filename: self.filename.as_deref(),
             location: self.location.as_deref(),
             filters: &self.filters.items,
+            default_filter: self.filters.default_index(),
             owner: self.owner.clone(),
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label.as_deref(),
+            preview: self.preview,
             multiple: false,
             dir: false,
         }
     }
 }
 
-impl DialogImpl for OpenSingleFile {
-    fn show(self) -> Result<Self::Output> {
+impl SelectImpl for OpenSingleFile {
+    fn select(self) -> Result<Option<FileSelection>> {
+        ensure_no_choices(&self.choices)?;
+
         super::process_init();
         let result = open_dialog(self.create())?;
-        Ok(result.map(|x| x.selected_file_path))
+        Ok(result.map(|x| FileSelection {
+            paths: vec![x.selected_file_path],
+            filter_index: get_filter_index(x.selected_file_type_index, &self.filters.items),
+            ..Default::default()
+        }))
     }
 
     #[cfg(feature = "async")]
-    async fn spawn(self) -> Result<Self::Output> {
-        self.show()
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        self.select()
     }
 }
 
@@ -46,27 +58,35 @@ impl OpenMultipleFile {
             filename: self.filename.as_deref(),
             location: self.location.as_deref(),
             filters: &self.filters.items,
+            default_filter: self.filters.default_index(),
             owner: self.owner.clone(),
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label.as_deref(),
+            preview: self.preview,
             multiple: true,
             dir: false,
         }
     }
 }
 
-impl DialogImpl for OpenMultipleFile {
-    fn show(self) -> Result<Self::Output> {
+impl SelectImpl for OpenMultipleFile {
+    fn select(self) -> Result<Option<FileSelection>> {
+        ensure_no_choices(&self.choices)?;
+
         super::process_init();
 
         let result = open_dialog(self.create())?;
-        match result {
-            Some(t) => Ok(t.selected_file_paths),
-            None => Ok(vec![]),
-        }
+        Ok(result.map(|x| FileSelection {
+            paths: x.selected_file_paths,
+            filter_index: get_filter_index(x.selected_file_type_index, &self.filters.items),
+            ..Default::default()
+        }))
     }
 
     #[cfg(feature = "async")]
-    async fn spawn(self) -> Result<Self::Output> {
-        self.show()
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        self.select()
     }
 }
 
@@ -77,24 +97,144 @@ impl OpenSingleDir {
             filename: self.filename.as_deref(),
             location: self.location.as_deref(),
             filters: &[],
+            default_filter: 0,
             owner: self.owner.clone(),
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label.as_deref(),
+            preview: false,
             multiple: false,
             dir: true,
         }
     }
 }
 
-impl DialogImpl for OpenSingleDir {
-    fn show(self) -> Result<Self::Output> {
+impl SelectImpl for OpenSingleDir {
+    fn select(self) -> Result<Option<FileSelection>> {
         super::process_init();
 
         let result = open_dialog(self.create())?;
-        Ok(result.map(|x| x.selected_file_path))
+        Ok(result.and_then(|x| FileSelection::from_dirs(vec![x.selected_file_path])))
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        self.select()
+    }
+}
+
+impl OpenMultipleDir {
+    fn create(&self) -> OpenDialogParams<'_> {
+        OpenDialogParams {
+            title: &self.title,
+            filename: self.filename.as_deref(),
+            location: self.location.as_deref(),
+            filters: &[],
+            default_filter: 0,
+            owner: self.owner.clone(),
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label.as_deref(),
+            preview: false,
+            multiple: true,
+            dir: true,
+        }
+    }
+}
+
+impl SelectImpl for OpenMultipleDir {
+    fn select(self) -> Result<Option<FileSelection>> {
+        super::process_init();
+
+        let result = open_dialog(self.create())?;
+        Ok(result.and_then(|x| FileSelection::from_dirs(x.selected_file_paths)))
+    }
+
+    #[cfg(feature = "async")]
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        self.select()
+    }
+}
+
+impl OpenSingleFileOrDir {
+    fn create(&self, dir: bool) -> OpenDialogParams<'_> {
+        OpenDialogParams {
+            title: &self.title,
+            filename: self.filename.as_deref(),
+            location: self.location.as_deref(),
+            filters: if dir { &[] } else { &self.filters.items },
+            default_filter: self.filters.default_index(),
+            owner: self.owner.clone(),
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label.as_deref(),
+            preview: self.preview,
+            multiple: false,
+            dir,
+        }
+    }
+}
+
+impl SelectImpl for OpenSingleFileOrDir {
+    fn select(self) -> Result<Option<FileSelection>> {
+        super::process_init();
+
+        let Some(dir) = ask_file_or_dir(&self.title, &self.owner)? else {
+            return Ok(None);
+        };
+
+        let result = open_dialog(self.create(dir))?;
+        Ok(result.map(|x| FileSelection {
+            paths: vec![x.selected_file_path],
+            filter_index: if dir {
+                None
+            } else {
+                get_filter_index(x.selected_file_type_index, &self.filters.items)
+            },
+            is_dir: dir,
+            ..Default::default()
+        }))
     }
 
     #[cfg(feature = "async")]
-    async fn spawn(self) -> Result<Self::Output> {
-        self.show()
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        self.select()
+    }
+}
+
+/// The file dialog cannot select both files and directories, so users are asked which one they
+/// want first. Returns whether users want a directory, or `None` if they cancelled.
+fn ask_file_or_dir(title: &str, owner: &UnsafeWindowHandle) -> Result<Option<bool>> {
+    use std::ffi::OsStr;
+    use std::iter::once;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+
+    use winapi::um::winuser::{IDNO, IDYES, MB_ICONQUESTION, MB_YESNOCANCEL, MessageBoxW};
+
+    let owner = unsafe { owner.as_win32().unwrap_or(null_mut()) };
+
+    let text: Vec<u16> = OsStr::new("Do you want to open a file? Choose \"No\" to open a folder.")
+        .encode_wide()
+        .chain(once(0))
+        .collect();
+
+    let caption: Vec<u16> = OsStr::new(title).encode_wide().chain(once(0)).collect();
+
+    let ret = super::with_visual_styles(|| unsafe {
+        MessageBoxW(
+            owner,
+            text.as_ptr(),
+            caption.as_ptr(),
+            MB_YESNOCANCEL | MB_ICONQUESTION,
+        )
+    });
+
+    match ret {
+        0 => Err(std::io::Error::last_os_error().into()),
+        IDYES => Ok(Some(false)),
+        IDNO => Ok(Some(true)),
+        _ => Ok(None),
     }
 }
 
@@ -105,22 +245,46 @@ impl SaveSingleFile {
             filename: self.filename.as_deref(),
             location: self.location.as_deref(),
             filters: &self.filters.items,
+            default_filter: self.filters.default_index(),
             owner: self.owner.clone(),
+            show_hidden: self.show_hidden,
+            can_create_directories: self.can_create_directories,
+            accept_label: self.accept_label.as_deref(),
+            confirm_overwrite: self.overwrite_policy == OverwritePolicy::Prompt,
         }
     }
 }
 
-impl DialogImpl for SaveSingleFile {
-    fn show(self) -> Result<Self::Output> {
+impl SelectImpl for SaveSingleFile {
+    fn select(self) -> Result<Option<FileSelection>> {
+        ensure_no_choices(&self.choices)?;
+
         super::process_init();
 
         let result = save_dialog(self.create())?;
-        Ok(result.map(|x| x.selected_file_path))
+        Ok(result.map(|x| FileSelection {
+            paths: vec![x.selected_file_path],
+            filter_index: get_filter_index(x.selected_filter_index, &self.filters.items),
+            ..Default::default()
+        }))
     }
 
     #[cfg(feature = "async")]
-    async fn spawn(self) -> Result<Self::Output> {
-        self.show()
+    async fn spawn_select(self) -> Result<Option<FileSelection>> {
+        self.select()
+    }
+
+    /// The dialog can only be told to prompt before overwriting, so `Deny` is handled here.
+    fn judge(&self, selection: &FileSelection) -> Verdict {
+        match selection.path() {
+            Some(path) if self.overwrite_policy == OverwritePolicy::Deny && path.exists() => {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                Verdict::Reject(format!(
+                    "A file named \"{name}\" already exists. Please choose another name."
+                ))
+            }
+            _ => Verdict::Accept,
+        }
     }
 }
 
@@ -129,14 +293,28 @@ pub struct OpenDialogParams<'a> {
     filename: Option<&'a str>,
     location: Option<&'a Path>,
     filters: &'a [FileFilter],
+    default_filter: usize,
     owner: UnsafeWindowHandle,
+    show_hidden: Option<bool>,
+    can_create_directories: Option<bool>,
+    accept_label: Option<&'a str>,
+    preview: bool,
     multiple: bool,
     dir: bool,
 }
 
 fn open_dialog(params: OpenDialogParams) -> Result<Option<OpenDialogResult>> {
-    let folder = params.location.and_then(resolve_tilde);
-    let folder = folder.as_deref().and_then(Path::to_str).unwrap_or("");
+    // The dialog always lets users create new folders
+    if params.can_create_directories == Some(false) {
+        return Err(Error::Unsupported("preventing directory creation"));
+    }
+
+    // The preview pane can only be toggled by users
+    if params.preview {
+        return Err(Error::Unsupported("preview pane"));
+    }
+
+    let folder = params.location.and_then(Path::to_str).unwrap_or("");
 
     let file_types: Vec<_> = get_dialog_file_types(params.filters);
     let file_types = file_types.iter().map(|t| (t.0, &*t.1)).collect();
@@ -150,6 +328,9 @@ fn open_dialog(params: OpenDialogParams) -> Result<Option<OpenDialogResult>> {
     if params.dir {
         options |= FOS_PICKFOLDERS;
     }
+    if params.show_hidden == Some(true) {
+        options |= FOS_FORCESHOWHIDDEN;
+    }
 
     let owner = unsafe { params.owner.as_win32() };
 
@@ -160,6 +341,8 @@ fn open_dialog(params: OpenDialogParams) -> Result<Option<OpenDialogResult>> {
         options,
         owner,
         title: params.title,
+        ok_button_label: params.accept_label.unwrap_or(""),
+        file_type_index: params.default_filter as u32 + 1,
         ..Default::default()
     };
 
@@ -173,25 +356,39 @@ pub struct SaveDialogParams<'a> {
     filename: Option<&'a str>,
     location: Option<&'a Path>,
     filters: &'a [FileFilter],
+    default_filter: usize,
     owner: UnsafeWindowHandle,
+    show_hidden: Option<bool>,
+    can_create_directories: Option<bool>,
+    accept_label: Option<&'a str>,
+    confirm_overwrite: bool,
 }
 
 fn save_dialog(params: SaveDialogParams) -> Result<Option<SaveDialogResult>> {
-    let folder = params.location.and_then(resolve_tilde);
-    let folder = folder.as_deref().and_then(Path::to_str).unwrap_or("");
+    // The dialog always lets users create new folders
+    if params.can_create_directories == Some(false) {
+        return Err(Error::Unsupported("preventing directory creation"));
+    }
+
+    let folder = params.location.and_then(Path::to_str).unwrap_or("");
 
     let file_types: Vec<_> = get_dialog_file_types(params.filters);
     let file_types = file_types.iter().map(|t| (t.0, &*t.1)).collect();
 
     let file_name = params.filename.unwrap_or("");
 
-    let default_extension = match params.filters.first() {
-        Some(first) => &first.extensions[0],
+    let default_extension = match params.filters.get(params.default_filter) {
+        Some(filter) => filter.default_extension().unwrap_or(""),
         _ => "",
     };
 
-    let options =
-        FOS_OVERWRITEPROMPT | FOS_PATHMUSTEXIST | FOS_NOREADONLYRETURN | FOS_STRICTFILETYPES;
+    let mut options = FOS_PATHMUSTEXIST | FOS_NOREADONLYRETURN | FOS_STRICTFILETYPES;
+    if params.confirm_overwrite {
+        options |= FOS_OVERWRITEPROMPT;
+    }
+    if params.show_hidden == Some(true) {
+        options |= FOS_FORCESHOWHIDDEN;
+    }
 
     let owner = unsafe { params.owner.as_win32() };
 
@@ -203,6 +400,8 @@ fn save_dialog(params: SaveDialogParams) -> Result<Option<SaveDialogResult>> {
         options,
         owner,
         title: params.title,
+        ok_button_label: params.accept_label.unwrap_or(""),
+        file_type_index: params.default_filter as u32 + 1,
         ..Default::default()
     };
 
@@ -222,6 +421,12 @@ fn get_dialog_file_types(filters: &[FileFilter]) -> Vec<(&str, String)> {
         .collect()
 }
 
+/// Converts the one-based index reported by the dialog.
+fn get_filter_index(index: u32, filters: &[FileFilter]) -> Option<usize> {
+    let index = (index as usize).checked_sub(1)?;
+    (index < filters.len()).then_some(index)
+}
+
 fn convert_result<T>(result: std::result::Result<T, DialogError>) -> Result<Option<T>> {
     match result {
         Ok(t) => Ok(Some(t)),
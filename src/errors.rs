@@ -18,6 +18,9 @@ pub enum Error {
     #[error("subprocess killed by signal")]
     Killed(OsString),
 
+    #[error("feature not supported by implementation")]
+    Unsupported(&'static str),
+
     #[error("other errors reported by implementation")]
     Other(String),
 }
@@ -14,6 +14,7 @@ pub trait NSSavePanelExt {
 
     fn set_delegate(&self, delegate: &SavePanelDelegate);
     fn set_title(&self, title: &str);
+    fn set_prompt(&self, prompt: &str);
     fn set_name_field_string_value(&self, value: &str);
     fn set_directory_url(&self, url: &Path);
 }
@@ -46,6 +47,11 @@ impl NSSavePanelExt for NSSavePanel {
         self.setTitle(Some(&title))
     }
 
+    fn set_prompt(&self, prompt: &str) {
+        let prompt = NSString::from_str(prompt);
+        self.setPrompt(Some(&prompt))
+    }
+
     fn set_name_field_string_value(&self, value: &str) {
         let value = NSString::from_str(value);
         self.setNameFieldStringValue(&value)
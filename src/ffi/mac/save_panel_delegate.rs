@@ -1,5 +1,7 @@
 use std::cell::Cell;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use objc2::rc::Retained as Id;
 use objc2::{DefinedClass, MainThreadOnly, Message, define_class, msg_send, sel};
@@ -12,13 +14,14 @@ use objc2_foundation::{
     NSArray, NSEdgeInsets, NSObject, NSObjectProtocol, NSPoint, NSRect, NSSize, NSString,
 };
 
-use super::{NSAlertExt, NSPopUpButtonExt, NSSavePanelExt, NSTextFieldExt};
-use crate::dialog::{FileFilter, FileFiltersBag};
+use super::{NSAlertExt, NSPopUpButtonExt, NSSavePanelExt, NSTextFieldExt, NSURLExt};
+use crate::dialog::{FileFilter, FileFiltersBag, OverwritePolicy};
 
 pub struct SavePanelDelegateIvars {
     accessory: Cell<Option<Id<NSView>>>,
     filters: FileFiltersBag,
-    selected: Cell<usize>,
+    overwrite_policy: OverwritePolicy,
+    selected: Arc<AtomicUsize>,
 }
 
 define_class! {
@@ -38,6 +41,7 @@ define_class! {
             _ok_flag: bool,
         ) -> Option<Id<NSString>> {
             unsafe { self.validate(sender, filename) }
+                .filter(|filename| self.validate_overwrite(sender, filename))
         }
     }
 
@@ -45,17 +49,22 @@ define_class! {
         #[unsafe(method(onItemSelected:))]
         unsafe fn on_item_selected(&self, sender: &NSPopUpButton) {
             let index = sender.indexOfSelectedItem();
-            self.ivars().selected.set(index as usize);
+            self.ivars().selected.store(index as usize, Ordering::Relaxed);
         }
     }
 }
 
 impl SavePanelDelegate {
-    pub fn attach(panel: &NSSavePanel, filters: &FileFiltersBag) -> Id<Self> {
+    pub fn attach(
+        panel: &NSSavePanel,
+        filters: &FileFiltersBag,
+        overwrite_policy: OverwritePolicy,
+    ) -> Id<Self> {
         let ivars = SavePanelDelegateIvars {
             accessory: Cell::new(None),
             filters: filters.to_owned(),
-            selected: Cell::new(0),
+            overwrite_policy,
+            selected: Arc::new(AtomicUsize::new(filters.default_index())),
         };
 
         let this = Self::alloc(panel.mtm()).set_ivars(ivars);
@@ -73,9 +82,18 @@ impl SavePanelDelegate {
         this
     }
 
+    /// Returns the index of the selected filter, which can still be read after the panel is
+    /// closed on another thread.
+    pub fn selected_index(&self) -> Arc<AtomicUsize> {
+        self.ivars().selected.clone()
+    }
+
     fn selected_filter(&self) -> Option<&FileFilter> {
         let ivars = self.ivars();
-        ivars.filters.items.get(ivars.selected.get())
+        ivars
+            .filters
+            .items
+            .get(ivars.selected.load(Ordering::Relaxed))
     }
 
     fn create_accessory(&self, filters: &FileFiltersBag) -> Id<NSView> {
@@ -84,7 +102,7 @@ impl SavePanelDelegate {
         let frame = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(480.0, 0.0));
         let dropdown = NSPopUpButton::new_with_frame(self.mtm(), frame);
         dropdown.addItemsWithTitles(&titles);
-        dropdown.selectItemAtIndex(0);
+        dropdown.selectItemAtIndex(filters.default_index() as isize);
         dropdown.set_action(sel!(onItemSelected:));
         dropdown.set_target(self);
 
@@ -128,8 +146,9 @@ impl SavePanelDelegate {
         }
 
         if path.extension().is_none() {
-            let ext = filter.extensions.first().unwrap();
-            return Some(NSString::from_str(&format!("{filename}{ext}")));
+            if let Some(ext) = filter.default_extension() {
+                return Some(NSString::from_str(&format!("{filename}{ext}")));
+            }
         }
 
         let explain = format!("Filename \"{}\" is not of type {}.", filename, filter.name);
@@ -151,4 +170,31 @@ impl SavePanelDelegate {
 
         None
     }
+
+    /// The panel always asks users before overwriting a file, so only the `Deny` policy needs to
+    /// be handled here.
+    fn validate_overwrite(&self, panel: &NSSavePanel, filename: &NSString) -> bool {
+        if self.ivars().overwrite_policy != OverwritePolicy::Deny {
+            return true;
+        }
+
+        let Some(directory) = panel.directoryURL().and_then(|url| url.to_path_buf()) else {
+            return true;
+        };
+
+        if !directory.join(filename.to_string()).exists() {
+            return true;
+        }
+
+        let explain = format!("\"{filename}\" already exists. Please choose another name.");
+
+        let alert = NSAlert::new(self.mtm());
+        alert.set_message_text("File Already Exists");
+        alert.set_informative_text(&explain);
+        unsafe { alert.setIcon(NSImage::imageNamed(NSImageNameCaution).as_deref()) };
+        alert.add_button("OK");
+        alert.run(Some(panel));
+
+        false
+    }
 }
@@ -9,5 +9,10 @@ pub(crate) mod utils;
 pub use builder::*;
 pub use dialog::Dialog;
 pub use dialog::file::*;
+pub use dialog::filter::*;
+pub use dialog::log::*;
 pub use dialog::message::*;
+pub use dialog::progress::*;
+pub use dialog::recent::*;
+pub use dialog::tray::*;
 pub use errors::*;
@@ -0,0 +1,5 @@
+/// Returns the name of the application, taken from the executable.
+pub fn app_name() -> Option<String> {
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.file_stem()?.to_string_lossy().into_owned())
+}
@@ -0,0 +1,148 @@
+/// Matches a filename against a shell-style glob pattern. Supports `*`, `?` and bracket
+/// expressions such as `[a-z]` or `[!0-9]`.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // Positions of the last star and the name it was tried at, to backtrack on mismatches
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        let step = match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(1),
+            Some('[') => match match_bracket(&pattern[p..], name[n]) {
+                Some((true, len)) => Some(len),
+                Some((false, _)) => None,
+                // An unterminated bracket is a literal
+                None => (name[n] == '[').then_some(1),
+            },
+            Some(c) => (*c == name[n]).then_some(1),
+            None => None,
+        };
+
+        match (step, star) {
+            (Some(len), _) => {
+                p += len;
+                n += 1;
+            }
+            (None, Some((sp, sn))) => {
+                star = Some((sp, sn + 1));
+                p = sp + 1;
+                n = sn + 1;
+            }
+            (None, None) => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Matches a character against the bracket expression at the start of the pattern. Returns
+/// whether it matched and the length of the expression, or `None` if it's unterminated.
+fn match_bracket(pattern: &[char], c: char) -> Option<(bool, usize)> {
+    let mut i = 1;
+
+    let negate = matches!(pattern.get(i), Some('!' | '^'));
+    if negate {
+        i += 1;
+    }
+
+    // A closing bracket right after the opening one is a literal
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        let lo = *pattern.get(i)?;
+        if lo == ']' && !first {
+            break;
+        }
+        first = false;
+
+        match (pattern.get(i + 1), pattern.get(i + 2)) {
+            (Some('-'), Some(&hi)) if hi != ']' => {
+                matched |= (lo..=hi).contains(&c);
+                i += 3;
+            }
+            _ => {
+                matched |= lo == c;
+                i += 1;
+            }
+        }
+    }
+
+    Some((matched != negate, i + 1))
+}
+
+/// Makes a glob pattern match regardless of case, by replacing each letter with a bracket
+/// expression of both cases, such as `*.[jJ][pP][gG]`. Existing bracket expressions are kept as
+/// they are, and so are letters whose other case is not a single character, such as `ß`.
+pub fn fold_case(pattern: &str) -> String {
+    let mut folded = String::new();
+    let mut in_bracket = false;
+
+    for c in pattern.chars() {
+        match c {
+            '[' if !in_bracket => in_bracket = true,
+            ']' if in_bracket => in_bracket = false,
+            _ => {}
+        }
+
+        let (mut lower, mut upper) = (c.to_lowercase(), c.to_uppercase());
+        match (lower.next(), lower.next(), upper.next(), upper.next()) {
+            (Some(lower), None, Some(upper), None) if !in_bracket && lower != upper => {
+                folded.push_str(&format!("[{lower}{upper}]"));
+            }
+            _ => folded.push(c),
+        }
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(glob_match("*.rs", "main.rs"));
+        assert!(glob_match("*.rs", ".rs"));
+        assert!(!glob_match("*.rs", "main.rs.bak"));
+        assert!(glob_match("*.tar.*", "archive.tar.gz"));
+        assert!(glob_match("a*b*c", "aXXbYYbZZc"));
+        assert!(glob_match("file?.txt", "file1.txt"));
+        assert!(!glob_match("file?.txt", "file.txt"));
+        assert!(glob_match("**", ""));
+        assert!(!glob_match("?", ""));
+        assert!(glob_match("Makefile", "Makefile"));
+        assert!(!glob_match("Makefile", "makefile"));
+    }
+
+    #[test]
+    fn matches_brackets() {
+        assert!(glob_match("[a-c]x", "bx"));
+        assert!(!glob_match("[a-c]x", "dx"));
+        assert!(glob_match("[!0-9]*", "a1"));
+        assert!(!glob_match("[^0-9]*", "1a"));
+        assert!(glob_match("[]]", "]"));
+        assert!(glob_match("[a-]", "-"));
+        assert!(glob_match("[abc", "[abc"));
+        assert!(!glob_match("[abc", "a"));
+    }
+
+    #[test]
+    fn folds_case() {
+        assert_eq!(fold_case("*.jpg"), "*.[jJ][pP][gG]");
+        assert_eq!(fold_case("[a-z]1"), "[a-z]1");
+        assert!(glob_match(&fold_case("*.jpg"), "PHOTO.JPG"));
+        assert!(glob_match(&fold_case("*.jpg"), "photo.Jpg"));
+        assert_eq!(fold_case("straße"), "[sS][tT][rR][aA]ß[eE]");
+        assert_eq!(fold_case("İ"), "İ");
+        assert!(glob_match(&fold_case("*.ß"), "x.ß"));
+    }
+}
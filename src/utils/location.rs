@@ -0,0 +1,341 @@
+use std::path::{Component, Path, PathBuf, is_separator};
+
+use dirs::home_dir;
+
+/// Expands a location given by users. Supports `file://` URIs, a leading `~` or `~user`, and
+/// `$VAR` or `${VAR}` variables. Relative paths are resolved against `base`, if any.
+pub fn expand_location(location: &Path, base: Option<&Path>) -> PathBuf {
+    let path = match location.to_str() {
+        Some(s) => match s.strip_prefix("file://") {
+            Some(uri) => match decode_file_uri(uri) {
+                Some(path) => path,
+                None => return location.to_path_buf(),
+            },
+            None => expand_home(&expand_vars(s)),
+        },
+        None => location.to_path_buf(),
+    };
+
+    match base {
+        Some(base) if path.is_relative() => base.join(path),
+        _ => path,
+    }
+}
+
+/// Unset variables are kept as they are, rather than removed as shells do.
+fn expand_vars(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let (name, len) = match rest[1..].strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 3),
+                None => ("", 1),
+            },
+            None => {
+                let end = rest[1..]
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len() - 1);
+                (&rest[1..=end], end + 1)
+            }
+        };
+
+        match std::env::var(name) {
+            Ok(value) if !name.is_empty() => result.push_str(&value),
+            _ => result.push_str(&rest[..len]),
+        }
+        rest = &rest[len..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    let Some(rest) = path.strip_prefix('~') else {
+        return PathBuf::from(path);
+    };
+
+    let (user, rest) = match rest.find(is_separator) {
+        Some(i) => (&rest[..i], &rest[i + 1..]),
+        None => (rest, ""),
+    };
+
+    let home = if user.is_empty() {
+        home_dir()
+    } else {
+        user_home_dir(user)
+    };
+
+    match home {
+        Some(home) if rest.is_empty() => home,
+        Some(home) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+#[cfg(unix)]
+fn user_home_dir(user: &str) -> Option<PathBuf> {
+    let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+
+    // Each line is `name:password:uid:gid:gecos:home:shell`
+    passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.len() > 5 && fields[0] == user).then(|| PathBuf::from(fields[5]))
+    })
+}
+
+#[cfg(not(unix))]
+fn user_home_dir(_user: &str) -> Option<PathBuf> {
+    None
+}
+
+/// Converts a `file://` URI to a path. Returns `None` for other schemes and remote hosts.
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+pub fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    decode_file_uri(uri.strip_prefix("file://")?)
+}
+
+/// Converts an absolute path to a `file://` URI, percent-encoding anything but unreserved
+/// characters, slashes and colons.
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+pub fn path_to_file_uri(path: &Path) -> Option<String> {
+    if !path.is_absolute() {
+        return None;
+    }
+
+    #[cfg(unix)]
+    let bytes = {
+        use std::os::unix::ffi::OsStrExt;
+
+        path.as_os_str().as_bytes().to_vec()
+    };
+
+    // Drive letters need a slash before them, as in `file:///C:/Users`, and UNC paths turn into
+    // a host, as in `file://server/share`
+    #[cfg(not(unix))]
+    let bytes = {
+        let path = path.to_str()?.replace('\\', "/");
+        match path.strip_prefix("//") {
+            Some(unc) => unc.as_bytes().to_vec(),
+            None => format!("/{path}").into_bytes(),
+        }
+    };
+
+    let mut uri = String::from("file://");
+    for byte in bytes {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    Some(uri)
+}
+
+/// Decodes the part of a `file://` URI after the scheme. Returns `None` for remote hosts.
+fn decode_file_uri(uri: &str) -> Option<PathBuf> {
+    let (host, path) = uri.split_at(uri.find('/').unwrap_or(uri.len()));
+    if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") {
+        return None;
+    }
+
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let bytes = percent_decode(path);
+
+    #[cfg(unix)]
+    {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+
+        Some(PathBuf::from(OsString::from_vec(bytes)))
+    }
+
+    #[cfg(not(unix))]
+    {
+        // Drive letters come after a slash, as in `file:///C:/Users`
+        let path = String::from_utf8_lossy(&bytes);
+        let path = match path.strip_prefix('/') {
+            Some(rest) if rest.as_bytes().get(1) == Some(&b':') => rest,
+            _ => &*path,
+        };
+
+        Some(PathBuf::from(path))
+    }
+}
+
+fn percent_decode(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|x| x.iter().all(u8::is_ascii_hexdigit));
+        match hex {
+            Some(hex) if bytes[i] == b'%' => {
+                let hex = std::str::from_utf8(hex).unwrap();
+                result.push(u8::from_str_radix(hex, 16).unwrap());
+                i += 3;
+            }
+            _ => {
+                result.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    result
+}
+
+/// Resolves symlinks and `..` components. Files that do not exist yet, as chosen in save dialogs,
+/// are resolved through their directory. On Windows, the `\\?\` prefix is removed from paths on
+/// drives, since most programs do not understand it.
+pub fn canonicalize(path: &Path) -> Option<PathBuf> {
+    let path = match std::fs::canonicalize(path) {
+        Ok(path) => path,
+        Err(_) => {
+            let name = path.file_name()?;
+            let parent = path.parent().filter(|x| !x.as_os_str().is_empty());
+            std::fs::canonicalize(parent.unwrap_or(Path::new(".")))
+                .ok()?
+                .join(name)
+        }
+    };
+
+    #[cfg(windows)]
+    if let Some(rest) = path.to_str().and_then(|x| x.strip_prefix(r"\\?\")) {
+        if rest.as_bytes().get(1) == Some(&b':') {
+            return Some(PathBuf::from(rest));
+        }
+    }
+
+    Some(path)
+}
+
+/// Returns the path relative to the base, going out of it with `..` if needed. Relative paths
+/// are resolved against the current directory first. Returns `None` if the paths are on
+/// different drives.
+pub fn relative_path(path: &Path, base: &Path) -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    let path = normalize(&cwd.join(path));
+    let base = normalize(&cwd.join(base));
+
+    let path: Vec<_> = path.components().collect();
+    let base: Vec<_> = base.components().collect();
+
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return None;
+    }
+
+    let mut result = PathBuf::new();
+    for _ in common..base.len() {
+        result.push("..");
+    }
+    result.extend(&path[common..]);
+
+    if result.as_os_str().is_empty() {
+        result.push(".");
+    }
+
+    Some(result)
+}
+
+/// Removes `.` and `..` components without looking at the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            component => result.push(component),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cargo sets this when running tests as well as when building them
+    const NAME: &str = env!("CARGO_PKG_NAME");
+
+    #[test]
+    fn expands_vars() {
+        assert_eq!(expand_vars("$CARGO_PKG_NAME/x"), format!("{NAME}/x"));
+        assert_eq!(expand_vars("a${CARGO_PKG_NAME}b"), format!("a{NAME}b"));
+        assert_eq!(expand_vars("$CARGO_PKG_NAME"), NAME);
+        assert_eq!(
+            expand_vars("$NATIVE_DIALOG_UNSET/x"),
+            "$NATIVE_DIALOG_UNSET/x"
+        );
+        assert_eq!(
+            expand_vars("${NATIVE_DIALOG_UNSET}"),
+            "${NATIVE_DIALOG_UNSET}"
+        );
+        assert_eq!(expand_vars("${CARGO_PKG_NAME"), "${CARGO_PKG_NAME");
+        assert_eq!(expand_vars("cost $5 or $"), "cost $5 or $");
+        assert_eq!(expand_vars("${}"), "${}");
+    }
+
+    #[test]
+    fn decodes_percents() {
+        assert_eq!(percent_decode("a%20b"), b"a b");
+        assert_eq!(percent_decode("%E2%82%ac"), "€".as_bytes());
+        assert_eq!(percent_decode("%ff"), [0xff]);
+        assert_eq!(percent_decode("100%"), b"100%");
+        assert_eq!(percent_decode("%2"), b"%2");
+        assert_eq!(percent_decode("%zz%41"), b"%zzA");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn round_trips_file_uris() {
+        let path = Path::new("/tmp/a b/ü#1?.txt");
+        let uri = path_to_file_uri(path).unwrap();
+        assert_eq!(uri, "file:///tmp/a%20b/%C3%BC%231%3F.txt");
+        assert_eq!(file_uri_to_path(&uri).as_deref(), Some(path));
+
+        assert_eq!(
+            file_uri_to_path("file://localhost/etc/hosts").as_deref(),
+            Some(Path::new("/etc/hosts"))
+        );
+        assert_eq!(file_uri_to_path("file://server/share"), None);
+        assert_eq!(file_uri_to_path("https://example.com/"), None);
+    }
+
+    #[test]
+    fn canonicalizes_missing_files() {
+        let dir = canonicalize(&std::env::temp_dir()).unwrap();
+        let missing = std::env::temp_dir()
+            .join(".")
+            .join("native-dialog-missing.txt");
+        assert_eq!(
+            canonicalize(&missing),
+            Some(dir.join("native-dialog-missing.txt"))
+        );
+        assert_eq!(canonicalize(&dir.join("missing/missing.txt")), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn makes_relative_paths() {
+        let relative = |path, base| relative_path(Path::new(path), Path::new(base));
+        assert_eq!(relative("/a/b/c", "/a"), Some(PathBuf::from("b/c")));
+        assert_eq!(relative("/a/b", "/a/c/d"), Some(PathBuf::from("../../b")));
+        assert_eq!(relative("/a/./b/../c", "/a"), Some(PathBuf::from("c")));
+        assert_eq!(relative("/a", "/a"), Some(PathBuf::from(".")));
+    }
+}
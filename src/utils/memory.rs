@@ -0,0 +1,54 @@
+use std::path::{Path, PathBuf};
+
+use super::app_name;
+
+/// Returns the directory last remembered for the key, if it still exists.
+pub fn recall_dir(key: &str) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(memory_file()?).ok()?;
+
+    read_records(&content)
+        .find(|(k, _)| *k == key)
+        .map(|(_, dir)| PathBuf::from(dir))
+        .filter(|dir| dir.is_dir())
+}
+
+/// Remembers the directory for the key, replacing the previous one.
+pub fn remember_dir(key: &str, dir: &Path) -> std::io::Result<()> {
+    let Some(file) = memory_file() else {
+        return Ok(());
+    };
+
+    // The records are separated by tabs and newlines, so anything containing them is skipped
+    let Some(dir) = dir.to_str() else {
+        return Ok(());
+    };
+    if [key, dir].iter().any(|x| x.contains(['\t', '\n'])) {
+        return Ok(());
+    }
+
+    let content = std::fs::read_to_string(&file).unwrap_or_default();
+    let mut records: Vec<_> = read_records(&content).filter(|(k, _)| *k != key).collect();
+    records.push((key, dir));
+
+    let mut content = String::new();
+    for (key, dir) in records {
+        content.push_str(&format!("{key}\t{dir}\n"));
+    }
+
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(file, content)
+}
+
+fn read_records(content: &str) -> impl Iterator<Item = (&str, &str)> {
+    content.lines().filter_map(|line| line.split_once('\t'))
+}
+
+/// Each application gets its own file, named after the executable.
+fn memory_file() -> Option<PathBuf> {
+    let dir = dirs::state_dir().or_else(dirs::data_local_dir)?;
+    let app = app_name()?;
+
+    Some(dir.join("native-dialog").join(format!("{app}.dirs")))
+}
@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use dirs::home_dir;
+
+/// A glob of the shared-mime-info database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+pub struct MimeGlob {
+    weight: u32,
+    mime_type: String,
+    glob: String,
+    case_sensitive: bool,
+}
+
+/// Resolves MIME types to the glob patterns registered for them in the shared-mime-info database.
+/// Wildcards such as `image/*` match all subtypes. Returns nothing if there is no database, which
+/// is usually the case on macOS and Windows.
+pub fn resolve_mime_globs(mime_types: &[String]) -> Vec<String> {
+    resolve_globs(mime_globs(), mime_types)
+}
+
+/// Guesses the MIME type of a file from its name with the shared-mime-info database.
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn guess_mime_type(path: &std::path::Path) -> Option<String> {
+    guess_type(mime_globs(), path.file_name()?.to_str()?)
+}
+
+/// Returns the globs of the database, which is read once per process.
+fn mime_globs() -> &'static [MimeGlob] {
+    static GLOBS: LazyLock<Vec<MimeGlob>> = LazyLock::new(|| {
+        let files = data_dirs()
+            .into_iter()
+            .filter_map(|dir| std::fs::read_to_string(dir.join("mime/globs2")).ok());
+        parse_globs(files)
+    });
+
+    &GLOBS
+}
+
+/// Parses `globs2` files, in the order of precedence. Each line is `weight:type:glob`, optionally
+/// followed by `:flags`. A `__NOGLOBS__` glob drops the globs of the type from the files of lower
+/// precedence, and is not a glob itself.
+fn parse_globs(files: impl IntoIterator<Item = String>) -> Vec<MimeGlob> {
+    let mut globs = vec![];
+    let mut cleared: HashSet<String> = HashSet::new();
+
+    for content in files {
+        let mut clearing = vec![];
+
+        for line in content.lines().filter(|line| !line.starts_with('#')) {
+            let mut fields = line.split(':');
+            let (Some(weight), Some(mime_type), Some(glob)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+
+            if glob == "__NOGLOBS__" {
+                clearing.push(mime_type.to_string());
+                continue;
+            }
+
+            if cleared.contains(mime_type) {
+                continue;
+            }
+
+            globs.push(MimeGlob {
+                weight: weight.parse().unwrap_or(50),
+                mime_type: mime_type.to_string(),
+                glob: glob.to_string(),
+                case_sensitive: fields
+                    .next()
+                    .is_some_and(|x| x.split(',').any(|x| x == "cs")),
+            });
+        }
+
+        cleared.extend(clearing);
+    }
+
+    globs
+}
+
+fn resolve_globs(globs: &[MimeGlob], mime_types: &[String]) -> Vec<String> {
+    let mut resolved: Vec<String> = vec![];
+
+    for glob in globs {
+        let wanted = mime_types.iter().any(|x| mime_matches(x, &glob.mime_type));
+        if wanted && !resolved.contains(&glob.glob) {
+            resolved.push(glob.glob.clone());
+        }
+    }
+
+    resolved
+}
+
+/// Only literal names and `*.ext` globs are considered, which covers nearly all the entries. The
+/// heaviest glob wins, then a case-sensitive one, and then the longest one.
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+fn guess_type(globs: &[MimeGlob], name: &str) -> Option<String> {
+    let lowercase = name.to_lowercase();
+
+    let matched = globs.iter().filter(|x| {
+        let (name, glob) = if x.case_sensitive {
+            (name, x.glob.clone())
+        } else {
+            (&*lowercase, x.glob.to_lowercase())
+        };
+
+        match glob.strip_prefix('*') {
+            Some(suffix) if suffix.starts_with('.') && !suffix.contains(['*', '?', '[']) => {
+                name.ends_with(suffix)
+            }
+            _ => !glob.contains(['*', '?', '[']) && name == glob,
+        }
+    });
+
+    let best = matched.max_by_key(|x| (x.weight, x.case_sensitive, x.glob.len()))?;
+    Some(best.mime_type.clone())
+}
+
+fn mime_matches(pattern: &str, mime_type: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some("*") => true,
+        Some(media) => mime_type.split('/').next() == Some(media),
+        None => pattern == mime_type,
+    }
+}
+
+/// Returns the XDG data directories, in the order of precedence.
+fn data_dirs() -> Vec<PathBuf> {
+    let data_home = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => home_dir().map(|home| home.join(".local/share")),
+    };
+
+    let data_dirs = match std::env::var("XDG_DATA_DIRS") {
+        Ok(dirs) if !dirs.is_empty() => dirs,
+        _ => String::from("/usr/local/share:/usr/share"),
+    };
+
+    data_home
+        .into_iter()
+        .chain(data_dirs.split(':').map(PathBuf::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCAL: &str = "\
+# Overrides by the user
+60:text/markdown:*.md
+50:text/x-readme:__NOGLOBS__
+";
+
+    const SYSTEM: &str = "\
+# This file was automatically generated
+50:text/plain:*.txt
+50:text/markdown:*.markdown
+50:text/x-readme:README
+50:text/x-c++src:*.C:cs
+50:text/x-csrc:*.c
+10:text/x-makefile:makefile
+40:application/x-compressed-tar:*.tar.gz
+50:application/gzip:*.gz
+50:image/png:*.png
+50:image/jpeg:*.jpg
+broken line
+";
+
+    fn globs() -> Vec<MimeGlob> {
+        parse_globs([LOCAL.to_string(), SYSTEM.to_string()])
+    }
+
+    #[test]
+    fn skips_cleared_types() {
+        let globs = globs();
+        assert!(!globs.iter().any(|x| x.glob.contains("NOGLOBS")));
+        assert!(!globs.iter().any(|x| x.mime_type == "text/x-readme"));
+        assert_eq!(globs.len(), 10);
+    }
+
+    #[test]
+    fn resolves_mime_types() {
+        let resolve = |x: &[&str]| {
+            let mime_types: Vec<String> = x.iter().map(ToString::to_string).collect();
+            resolve_globs(&globs(), &mime_types)
+        };
+
+        assert_eq!(resolve(&["text/markdown"]), ["*.md", "*.markdown"]);
+        assert_eq!(
+            resolve(&["image/*", "text/plain"]),
+            ["*.txt", "*.png", "*.jpg"]
+        );
+        assert_eq!(resolve(&["text/x-readme"]), Vec::<String>::new());
+        assert_eq!(resolve(&["*/*"]).len(), 10);
+    }
+
+    #[test]
+    fn guesses_mime_types() {
+        let guess = |name| guess_type(&globs(), name);
+
+        assert_eq!(guess("notes.MD").as_deref(), Some("text/markdown"));
+        assert_eq!(guess("main.c").as_deref(), Some("text/x-csrc"));
+        assert_eq!(guess("main.C").as_deref(), Some("text/x-c++src"));
+        assert_eq!(guess("Makefile").as_deref(), Some("text/x-makefile"));
+        assert_eq!(guess("a.tar.gz").as_deref(), Some("application/gzip"));
+        assert_eq!(guess("README"), None);
+        assert_eq!(guess("photo.webp"), None);
+    }
+}
@@ -1,7 +1,24 @@
-#[cfg(not(target_os = "macos"))]
-mod tilde;
-#[cfg(not(target_os = "macos"))]
-pub use tilde::*;
+mod app;
+pub use app::*;
+
+mod location;
+pub use location::*;
+
+mod memory;
+pub use memory::*;
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod xbel;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub use xbel::*;
+
+#[cfg(unix)]
+mod glob;
+#[cfg(unix)]
+pub use glob::*;
+
+mod mime;
+pub use mime::*;
 
 mod window;
 pub use window::*;
@@ -0,0 +1,361 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{file_uri_to_path, path_to_file_uri};
+
+const EMPTY_XBEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+</xbel>
+"#;
+
+/// A file listed in an XBEL document, such as `recently-used.xbel`.
+pub struct Bookmark {
+    pub path: PathBuf,
+    pub mime_type: Option<String>,
+    pub modified: Option<SystemTime>,
+    pub apps: Vec<String>,
+}
+
+/// Reads the local files listed in an XBEL document. Other bookmarks are skipped.
+pub fn read_bookmarks(xbel: &str) -> Vec<Bookmark> {
+    let mut bookmarks = vec![];
+
+    for range in find_elements(xbel, "bookmark") {
+        let element = &xbel[range];
+        let Some(path) = attr(element, "href").and_then(|x| file_uri_to_path(&x)) else {
+            continue;
+        };
+
+        let mime_type = find_elements(element, "mime:mime-type")
+            .next()
+            .and_then(|x| attr(&element[x], "type"));
+
+        let apps = find_elements(element, "bookmark:application")
+            .filter_map(|x| attr(&element[x], "name"))
+            .collect();
+
+        bookmarks.push(Bookmark {
+            path,
+            mime_type,
+            modified: attr(element, "modified").and_then(|x| parse_timestamp(&x)),
+            apps,
+        });
+    }
+
+    bookmarks
+}
+
+/// Adds a local file to an XBEL document on behalf of an application, or marks it as used again
+/// if it is already there. The rest of the document is kept as it is. Returns `None` if the path
+/// has no file URI, or if the document is cut short, as its bookmarks would be lost otherwise.
+pub fn add_bookmark(
+    xbel: &str,
+    path: &Path,
+    mime_type: &str,
+    app: &str,
+    now: SystemTime,
+) -> Option<String> {
+    let href = path_to_file_uri(path)?;
+    let stamp = format_timestamp(now);
+
+    let xbel = if xbel.trim().is_empty() {
+        EMPTY_XBEL
+    } else if xbel.contains("</xbel>") {
+        xbel
+    } else {
+        return None;
+    };
+
+    let application = |count: u32| {
+        format!(
+            r#"<bookmark:application name="{}" exec="&apos;{} %u&apos;" modified="{stamp}" count="{count}"/>"#,
+            escape(app),
+            escape(app),
+        )
+    };
+
+    let existing = find_elements(xbel, "bookmark")
+        .find(|x| attr(&xbel[x.clone()], "href").as_deref() == Some(&*href));
+
+    let Some(range) = existing else {
+        let end = xbel.rfind("</xbel>")?;
+        let bookmark = format!(
+            concat!(
+                "  <bookmark href=\"{href}\" added=\"{stamp}\" modified=\"{stamp}\" visited=\"{stamp}\">\n",
+                "    <info>\n",
+                "      <metadata owner=\"http://freedesktop.org\">\n",
+                "        <mime:mime-type type=\"{mime_type}\"/>\n",
+                "        <bookmark:applications>\n",
+                "          {application}\n",
+                "        </bookmark:applications>\n",
+                "      </metadata>\n",
+                "    </info>\n",
+                "  </bookmark>\n",
+            ),
+            href = href,
+            stamp = stamp,
+            mime_type = escape(mime_type),
+            application = application(1),
+        );
+        return Some(format!("{}{bookmark}{}", &xbel[..end], &xbel[end..]));
+    };
+
+    let element = &xbel[range.clone()];
+    let start_tag_len = element.find('>')? + 1;
+    let start_tag = set_attr(&element[..start_tag_len], "modified", &stamp);
+    let start_tag = set_attr(&start_tag, "visited", &stamp);
+    let content = &element[start_tag_len..];
+
+    let ours = find_elements(content, "bookmark:application")
+        .find(|x| attr(&content[x.clone()], "name").as_deref() == Some(app));
+
+    let content = match ours {
+        Some(x) => {
+            let count = attr(&content[x.clone()], "count").and_then(|x| x.parse().ok());
+            let application = application(count.unwrap_or(0) + 1);
+            format!("{}{application}{}", &content[..x.start], &content[x.end..])
+        }
+        None => {
+            let end = content.find("</bookmark:applications>")?;
+            let application = application(1);
+            format!(
+                "{}  {application}\n        {}",
+                &content[..end],
+                &content[end..]
+            )
+        }
+    };
+
+    Some(format!(
+        "{}{start_tag}{content}{}",
+        &xbel[..range.start],
+        &xbel[range.end..]
+    ))
+}
+
+/// Finds the elements of the name, including their content. Nested elements of the same name are
+/// not supported, which XBEL does not have for bookmarks.
+fn find_elements<'a>(xml: &'a str, name: &'a str) -> impl Iterator<Item = Range<usize>> + 'a {
+    let open = format!("<{name}");
+    let close = format!("</{name}>");
+    let mut offset = 0;
+
+    std::iter::from_fn(move || {
+        loop {
+            let start = offset + xml[offset..].find(&open)?;
+            let after = start + open.len();
+            offset = after;
+
+            // Skip elements whose names only start with the name
+            if !xml[after..].starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/') {
+                continue;
+            }
+
+            let tag_end = after + xml[after..].find('>')? + 1;
+            let end = if xml[..tag_end].ends_with("/>") {
+                tag_end
+            } else {
+                tag_end + xml[tag_end..].find(&close)? + close.len()
+            };
+
+            offset = end;
+            return Some(start..end);
+        }
+    })
+}
+
+/// Reads an attribute of the first tag of an element, unescaped.
+fn attr(element: &str, name: &str) -> Option<String> {
+    let tag = &element[..element.find('>')?];
+    let (start, end) = attr_range(tag, name)?;
+    Some(unescape(&tag[start..end]))
+}
+
+/// Sets an attribute of a tag, appending it if missing. The value must not need escaping.
+fn set_attr(tag: &str, name: &str, value: &str) -> String {
+    match attr_range(tag, name) {
+        Some((start, end)) => format!("{}{value}{}", &tag[..start], &tag[end..]),
+        None => {
+            let end = tag.len() - if tag.ends_with("/>") { 2 } else { 1 };
+            format!("{} {name}=\"{value}\"{}", &tag[..end], &tag[end..])
+        }
+    }
+}
+
+/// Returns where the value of an attribute is within a tag.
+fn attr_range(tag: &str, name: &str) -> Option<(usize, usize)> {
+    let pattern = format!("{name}=\"");
+    let mut offset = 0;
+
+    while let Some(found) = tag[offset..].find(&pattern) {
+        let start = offset + found;
+        offset = start + pattern.len();
+
+        if tag[..start].ends_with(char::is_whitespace) {
+            let end = offset + tag[offset..].find('"')?;
+            return Some((offset, end));
+        }
+    }
+
+    None
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Formats a time as in `2025-01-31T12:00:00Z`.
+fn format_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let (days, secs) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Parses a time as in `2025-01-31T12:00:00Z`. Fractions of seconds are ignored.
+fn parse_timestamp(s: &str) -> Option<SystemTime> {
+    let field = |range: Range<usize>| s.get(range)?.parse::<i64>().ok();
+
+    let days = days_from_civil(field(0..4)?, field(5..7)?, field(8..10)?);
+    let secs = days * 86400 + field(11..13)? * 3600 + field(14..16)? * 60 + field(17..19)?;
+
+    let secs = u64::try_from(secs).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Converts days since 1970-01-01 to a date in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// Converts a date in the proleptic Gregorian calendar to days since 1970-01-01.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146097 + doe - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOON: u64 = 1738324800;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(format_timestamp(at(NOON)), "2025-01-31T12:00:00Z");
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            parse_timestamp("2025-01-31T12:00:00.123456Z"),
+            Some(at(NOON))
+        );
+        assert_eq!(
+            parse_timestamp("2024-02-29T23:59:59Z"),
+            Some(at(1709251199))
+        );
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn adds_bookmarks_to_empty_documents() {
+        let path = Path::new("/home/user/a & b.txt");
+        let xbel = add_bookmark("", path, "text/plain", "app", at(NOON)).unwrap();
+
+        let bookmarks = read_bookmarks(&xbel);
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].path, path);
+        assert_eq!(bookmarks[0].mime_type.as_deref(), Some("text/plain"));
+        assert_eq!(bookmarks[0].modified, Some(at(NOON)));
+        assert_eq!(bookmarks[0].apps, ["app"]);
+    }
+
+    #[test]
+    fn updates_existing_bookmarks() {
+        let path = Path::new("/tmp/x.txt");
+        let xbel = add_bookmark("", path, "text/plain", "app", at(NOON)).unwrap();
+        let xbel = add_bookmark(&xbel, path, "text/plain", "app", at(NOON + 60)).unwrap();
+        let xbel = add_bookmark(&xbel, path, "text/plain", "<other>", at(NOON + 120)).unwrap();
+        let xbel = add_bookmark(&xbel, Path::new("/tmp/y"), "text/plain", "app", at(0)).unwrap();
+
+        let bookmarks = read_bookmarks(&xbel);
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].modified, Some(at(NOON + 120)));
+        assert_eq!(bookmarks[0].apps, ["app", "<other>"]);
+        assert_eq!(bookmarks[1].path, Path::new("/tmp/y"));
+
+        let element = find_elements(&xbel, "bookmark:application").next().unwrap();
+        assert_eq!(attr(&xbel[element], "count").as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn keeps_other_content() {
+        let xbel = concat!(
+            "<?xml version=\"1.0\"?>\n<xbel version=\"1.0\">\n",
+            "  <bookmark href=\"https://example.com/\" modified=\"2020-01-01T00:00:00Z\"/>\n",
+            "  <bookmarkish href=\"file:///nope\"/>\n",
+            "</xbel>\n",
+        );
+
+        let updated = add_bookmark(xbel, Path::new("/tmp/x"), "text/plain", "app", at(0)).unwrap();
+        assert!(updated.starts_with(&xbel[..xbel.rfind("</xbel>").unwrap()]));
+        assert!(updated.ends_with("</xbel>\n"));
+
+        let bookmarks = read_bookmarks(&updated);
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].path, Path::new("/tmp/x"));
+    }
+
+    #[test]
+    fn keeps_incomplete_documents() {
+        let path = Path::new("/tmp/x");
+        let xbel =
+            "<?xml version=\"1.0\"?>\n<xbel version=\"1.0\">\n  <bookmark href=\"file:///tmp/x\"";
+
+        assert_eq!(add_bookmark(xbel, path, "text/plain", "app", at(0)), None);
+        assert!(add_bookmark(" \n", path, "text/plain", "app", at(0)).is_some());
+    }
+}